@@ -2,187 +2,2952 @@
 
 pub mod tools {
     use base64::prelude::*;
+    use serde::{Serialize, Serializer};
     use serde_json::Value;
     use sha2::{Digest, Sha256};
-    use solana_account_decoder::UiAccountEncoding;
-    use solana_cli_output::display::println_transaction;
+    use solana_account_decoder::{UiAccountEncoding, UiDataSliceConfig};
     use solana_client::{
-        rpc_client::RpcClient,
-        rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+        pubsub_client::{ProgramSubscription, PubsubClient},
+        rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+        rpc_config::{
+            RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcSimulateTransactionAccountsConfig,
+            RpcSimulateTransactionConfig, RpcTransactionConfig,
+        },
         rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType},
+        rpc_request::RpcRequest,
+        rpc_response::{OptionalContext, RpcConfirmedTransactionStatusWithSignature, RpcKeyedAccount},
     };
     use solana_sdk::{account::Account, pubkey::Pubkey};
     use solana_sdk::{
-        message::Message,
-        transaction::{Transaction, VersionedTransaction},
+        address_lookup_table::state::AddressLookupTable,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::{Message, VersionedMessage},
+        signature::Signature,
+        transaction::VersionedTransaction,
     };
-    use std::collections::HashMap;
+    use solana_transaction_status::{
+        EncodedConfirmedTransactionWithStatusMeta, UiInnerInstructions, UiInstruction, UiLoadedAddresses,
+        UiTransactionEncoding, UiTransactionTokenBalance,
+    };
+    use std::collections::{BTreeMap, HashMap};
     use std::error::Error;
+    use std::fmt;
+    use std::io::Read as _;
+    use std::path::{Path, PathBuf};
     use std::str::FromStr as _;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex, PoisonError};
+    use std::thread;
+
+    const DISCRIMINATOR_LEN: usize = 8;
+    const MULTIPLE_ACCOUNTS_BATCH_SIZE: usize = 100;
+
+    /// Address lookup table contents keyed by table address.
+    pub type LookupTables = HashMap<Pubkey, Vec<Pubkey>>;
+
+    /// Instruction indexes in a message paired with their IDL decoding result.
+    pub type IdlInstructionResults = Vec<(usize, Result<DecodedIdlInstruction, String>)>;
+
+    /// Events found in a transaction's logs, each with its own decoding result.
+    pub type EventResults = Vec<Result<DecodedEvent, String>>;
+
+    /// Program accounts together with the slot they were read at.
+    pub type SlotAccounts = (u64, Vec<(Pubkey, Account)>);
+
+    // Get program accounts by discriminator
+    pub fn get_program_accounts_with_discrim(
+        connection: &RpcClient,
+        program_address: &str,
+        discrim: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>, Box<dyn Error>> {
+        let discrim_base64 = BASE64_STANDARD.encode(discrim);
+        let memcmp = RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Base64(discrim_base64)));
+        
+        let config = create_program_account_config(vec![memcmp]);
+        
+        let accounts = connection
+            .get_program_accounts_with_config(&Pubkey::from_str(program_address)?, config)
+            .map_err(|e| format!("RPC error: {:?}", e))?;
+            
+        Ok(accounts)
+    }
+
+    /// Subscribes to program account updates by discriminator and optional (offset, value) filters.
+    pub fn subscribe_program_accounts_with_discrim(
+        websocket_url: &str,
+        program_address: &str,
+        discrim: &[u8],
+        variable_filters: &[(usize, Vec<u8>)],
+    ) -> Result<ProgramSubscription, Box<dyn Error>> {
+        let config = create_program_account_config(build_memcmp_filters(discrim, variable_filters));
+        let subscription =
+            PubsubClient::program_subscribe(websocket_url, &Pubkey::from_str(program_address)?, Some(config))?;
+
+        Ok(subscription)
+    }
+
+    /// Gets program accounts by discriminator and (offset, value) filters, along with the slot they were read at.
+    pub fn get_program_accounts_with_slot(
+        connection: &RpcClient,
+        program_address: &str,
+        discrim: &[u8],
+        variable_filters: &[(usize, Vec<u8>)],
+    ) -> Result<SlotAccounts, Box<dyn Error>> {
+        let mut config = create_program_account_config(build_memcmp_filters(discrim, variable_filters));
+        config.account_config.commitment = Some(connection.commitment());
+        config.with_context = Some(true);
+
+        let response: OptionalContext<Vec<RpcKeyedAccount>> = connection.send(
+            RpcRequest::GetProgramAccounts,
+            serde_json::json!([Pubkey::from_str(program_address)?.to_string(), config]),
+        )?;
+        let OptionalContext::Context(response) = response else {
+            return Err("RPC response did not include a context slot".into());
+        };
+
+        let mut accounts = Vec::with_capacity(response.value.len());
+        for keyed_account in response.value {
+            let pubkey = Pubkey::from_str(&keyed_account.pubkey)?;
+            let account: Account = keyed_account
+                .account
+                .decode()
+                .ok_or_else(|| format!("Failed to decode account data for {}", pubkey))?;
+            accounts.push((pubkey, account));
+        }
+
+        Ok((response.context.slot, accounts))
+    }
+
+    /// Fetches accounts by address with getMultipleAccounts, splitting the request into batches the RPC accepts.
+    pub fn get_multiple_accounts_batched(
+        connection: &RpcClient,
+        addresses: &[Pubkey],
+    ) -> Result<Vec<Option<Account>>, Box<dyn Error>> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for batch in addresses.chunks(MULTIPLE_ACCOUNTS_BATCH_SIZE) {
+            accounts.extend(connection.get_multiple_accounts(batch)?);
+        }
+        Ok(accounts)
+    }
+
+    /// Gets program accounts by discriminator with lamports and owner only, leaving out the account data.
+    pub fn get_program_accounts_without_data(
+        connection: &RpcClient,
+        program_address: &str,
+        discrim: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>, Box<dyn Error>> {
+        let mut config = create_program_account_config(build_memcmp_filters(discrim, &[]));
+        config.account_config.data_slice = Some(UiDataSliceConfig { offset: 0, length: 0 });
+        Ok(connection.get_program_accounts_with_config(&Pubkey::from_str(program_address)?, config)?)
+    }
+
+    // Helper to build memcmp filters for a discriminator and (offset, value) pairs
+    fn build_memcmp_filters(discrim: &[u8], variable_filters: &[(usize, Vec<u8>)]) -> Vec<RpcFilterType> {
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new(
+            0,
+            MemcmpEncodedBytes::Base64(BASE64_STANDARD.encode(discrim)),
+        ))];
+        for (offset, value) in variable_filters {
+            filters.push(RpcFilterType::Memcmp(Memcmp::new(
+                *offset,
+                MemcmpEncodedBytes::Base64(BASE64_STANDARD.encode(value)),
+            )));
+        }
+        filters
+    }
+
+    // Helper function to create program account config with filters
+    fn create_program_account_config(filters: Vec<RpcFilterType>) -> RpcProgramAccountsConfig {
+        RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// How many accounts of one IDL account type a program owns and the lamports they hold.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct AccountTypeCensus {
+        pub account: String,
+        pub discriminator: String,
+        pub count: usize,
+        pub lamports: u64,
+    }
+
+    /// A program-owned account whose discriminator matches no IDL account type.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct UnknownAccount {
+        pub pubkey: String,
+        pub discriminator: String,
+        pub lamports: u64,
+    }
+
+    /// Account counts per IDL type, plus the program's accounts that match no type.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct ProgramCensus {
+        pub types: Vec<AccountTypeCensus>,
+        pub unknown: Vec<UnknownAccount>,
+    }
+
+    /// Counts a program's accounts per IDL account type and finds the accounts that match none of them.
+    pub fn program_census(
+        connection: &RpcClient,
+        idl: &str,
+        program_address: &str,
+    ) -> Result<ProgramCensus, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let program = Pubkey::from_str(program_address)?;
+        let accounts = idl_json
+            .get("accounts")
+            .and_then(|a| a.as_array())
+            .ok_or("IDL does not contain 'accounts' or it is not an array")?;
+
+        // Counting only needs lamports, so each type is fetched without any account data
+        let mut types = Vec::with_capacity(accounts.len());
+        for account in accounts {
+            let name = account.get("name").and_then(|n| n.as_str()).ok_or("Account has no name")?;
+            let discriminator = idl_discriminator(account, calculate_discriminator).ok_or("Account has an invalid discriminator")?;
+            let matched = get_program_accounts_without_data(connection, program_address, &discriminator)?;
+            types.push(AccountTypeCensus {
+                account: name.to_string(),
+                discriminator: to_hex(&discriminator),
+                count: matched.len(),
+                lamports: matched.iter().map(|(_, account)| account.lamports).sum(),
+            });
+        }
+
+        // Unknown accounts are found by reading just the discriminator of every program account
+        let mut config = create_program_account_config(Vec::new());
+        config.filters = None;
+        config.account_config.data_slice = Some(UiDataSliceConfig { offset: 0, length: DISCRIMINATOR_LEN });
+        let known: Vec<String> = types.iter().map(|census| census.discriminator.clone()).collect();
+        let unknown = connection
+            .get_program_accounts_with_config(&program, config)?
+            .into_iter()
+            .map(|(pubkey, account)| UnknownAccount {
+                pubkey: pubkey.to_string(),
+                discriminator: to_hex(&account.data[..account.data.len().min(DISCRIMINATOR_LEN)]),
+                lamports: account.lamports,
+            })
+            .filter(|account| !known.contains(&account.discriminator))
+            .collect();
+
+        Ok(ProgramCensus { types, unknown })
+    }
+
+    // Helper to format bytes as lowercase hex
+    fn to_hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// A transaction message decoded into its header, account keys and instructions.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct DecodedMessage {
+        pub version: String,
+        pub header: DecodedHeader,
+        pub account_keys: Vec<DecodedAccountKey>,
+        pub recent_blockhash: String,
+        pub instructions: Vec<DecodedInstruction>,
+        pub address_table_lookups: Vec<DecodedAddressTableLookup>,
+    }
+
+    /// The signature and read-only account counts from a message header.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct DecodedHeader {
+        pub num_required_signatures: u8,
+        pub num_readonly_signed_accounts: u8,
+        pub num_readonly_unsigned_accounts: u8,
+    }
+
+    /// An account referenced by a message, with its signer and writable flags.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct DecodedAccountKey {
+        pub pubkey: String,
+        pub signer: bool,
+        pub writable: bool,
+    }
+
+    /// A v0 address table lookup and whether its indexes could be resolved to addresses.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct DecodedAddressTableLookup {
+        pub account_key: String,
+        pub writable_indexes: Vec<u8>,
+        pub readonly_indexes: Vec<u8>,
+        pub resolved: bool,
+    }
+
+    /// An instruction with its program and accounts resolved to keys.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct DecodedInstruction {
+        pub program_id: String,
+        pub accounts: Vec<DecodedAccountKey>,
+        #[serde(serialize_with = "serialize_base64")]
+        pub data: Vec<u8>,
+    }
+
+    impl fmt::Display for DecodedAccountKey {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let flags: Vec<&str> = [(self.signer, "signer"), (self.writable, "writable")]
+                .into_iter()
+                .filter_map(|(set, name)| set.then_some(name))
+                .collect();
+            if flags.is_empty() {
+                write!(f, "{}", self.pubkey)
+            } else {
+                write!(f, "{} ({})", self.pubkey, flags.join(", "))
+            }
+        }
+    }
+
+    impl fmt::Display for DecodedMessage {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            writeln!(
+                f,
+                "Header: {} required signature(s), {} readonly signed, {} readonly unsigned",
+                self.header.num_required_signatures,
+                self.header.num_readonly_signed_accounts,
+                self.header.num_readonly_unsigned_accounts
+            )?;
+            writeln!(f, "Version: {}", self.version)?;
+            writeln!(f, "Recent blockhash: {}", self.recent_blockhash)?;
+            writeln!(f, "Account keys:")?;
+            for (i, key) in self.account_keys.iter().enumerate() {
+                writeln!(f, "  [{}] {}", i, key)?;
+            }
+            writeln!(f, "Instructions:")?;
+            for (i, instruction) in self.instructions.iter().enumerate() {
+                writeln!(f, "  [{}] Program: {}", i, instruction.program_id)?;
+                for account in &instruction.accounts {
+                    writeln!(f, "      Account: {}", account)?;
+                }
+                writeln!(f, "      Data: {}", BASE64_STANDARD.encode(&instruction.data))?;
+            }
+            if !self.address_table_lookups.is_empty() {
+                writeln!(f, "Address table lookups:")?;
+                for lookup in &self.address_table_lookups {
+                    writeln!(
+                        f,
+                        "  {} writable {:?} readonly {:?}{}",
+                        lookup.account_key,
+                        lookup.writable_indexes,
+                        lookup.readonly_indexes,
+                        if lookup.resolved { "" } else { " (unresolved)" }
+                    )?;
+                }
+            }
+            Ok(())
+        }
+    }
+
+    // Serialize raw bytes as a base64 string
+    fn serialize_base64<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&BASE64_STANDARD.encode(bytes))
+    }
+
+    /// Decodes a base64 encoded legacy or v0 message, leaving lookup table addresses unresolved.
+    pub fn decode_message(message_data: &str) -> Result<DecodedMessage, Box<dyn Error>> {
+        decode_message_with_lookup_tables(message_data, &LookupTables::new())
+    }
+
+    /// Decodes a base64 encoded legacy or v0 message, resolving lookup table indexes from the given tables.
+    pub fn decode_message_with_lookup_tables(
+        message_data: &str,
+        lookup_tables: &LookupTables,
+    ) -> Result<DecodedMessage, Box<dyn Error>> {
+        let message = deserialize_message(message_data)?;
+        decode_versioned_message(&message, lookup_tables)
+    }
+
+    // Helper to deserialize a base64 encoded legacy or v0 message
+    fn deserialize_message(message_data: &str) -> Result<VersionedMessage, Box<dyn Error>> {
+        let decoded_data = BASE64_STANDARD
+            .decode(message_data.trim())
+            .map_err(|e| format!("Failed to decode message: {}", e))?;
+        // VersionedMessage detects the version prefix and falls back to the legacy format
+        let message: VersionedMessage =
+            bincode::deserialize(&decoded_data).map_err(|e| format!("Failed to deserialize message: {}", e))?;
+        Ok(message)
+    }
+
+    /// Decodes an already deserialized message, resolving lookup table indexes from the given tables.
+    pub fn decode_versioned_message(
+        message: &VersionedMessage,
+        lookup_tables: &LookupTables,
+    ) -> Result<DecodedMessage, Box<dyn Error>> {
+        let message_header = message.header();
+        let header = DecodedHeader {
+            num_required_signatures: message_header.num_required_signatures,
+            num_readonly_signed_accounts: message_header.num_readonly_signed_accounts,
+            num_readonly_unsigned_accounts: message_header.num_readonly_unsigned_accounts,
+        };
+        let mut account_keys = decode_account_keys(&header, message.static_account_keys());
+
+        // Loaded addresses follow the static keys: all writable lookups first, then all readonly ones
+        let mut address_table_lookups = Vec::new();
+        let mut writable_loaded = Vec::new();
+        let mut readonly_loaded = Vec::new();
+        for lookup in message.address_table_lookups().unwrap_or_default() {
+            let table = lookup_tables.get(&lookup.account_key);
+            let resolve = |index: u8| -> Result<String, Box<dyn Error>> {
+                match table {
+                    Some(addresses) => addresses
+                        .get(index as usize)
+                        .map(|address| address.to_string())
+                        .ok_or_else(|| format!("Lookup table {} has no index {}", lookup.account_key, index).into()),
+                    None => Ok(format!("{}[{}]", lookup.account_key, index)),
+                }
+            };
+            for &index in &lookup.writable_indexes {
+                writable_loaded.push(resolve(index)?);
+            }
+            for &index in &lookup.readonly_indexes {
+                readonly_loaded.push(resolve(index)?);
+            }
+            address_table_lookups.push(DecodedAddressTableLookup {
+                account_key: lookup.account_key.to_string(),
+                writable_indexes: lookup.writable_indexes.clone(),
+                readonly_indexes: lookup.readonly_indexes.clone(),
+                resolved: table.is_some(),
+            });
+        }
+        for (pubkey, writable) in writable_loaded
+            .into_iter()
+            .map(|k| (k, true))
+            .chain(readonly_loaded.into_iter().map(|k| (k, false)))
+        {
+            account_keys.push(DecodedAccountKey {
+                pubkey,
+                signer: false,
+                writable,
+            });
+        }
+
+        let instructions = message
+            .instructions()
+            .iter()
+            .map(|instruction| {
+                resolve_instruction(
+                    &account_keys,
+                    instruction.program_id_index,
+                    &instruction.accounts,
+                    instruction.data.clone(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let version = match message {
+            VersionedMessage::Legacy(_) => "legacy".to_string(),
+            VersionedMessage::V0(_) => "0".to_string(),
+        };
+
+        Ok(DecodedMessage {
+            version,
+            header,
+            account_keys,
+            recent_blockhash: message.recent_blockhash().to_string(),
+            instructions,
+            address_table_lookups,
+        })
+    }
+
+    // Helper to resolve an instruction's program and account indexes against the message's account keys
+    fn resolve_instruction(
+        account_keys: &[DecodedAccountKey],
+        program_id_index: u8,
+        account_indexes: &[u8],
+        data: Vec<u8>,
+    ) -> Result<DecodedInstruction, Box<dyn Error>> {
+        let program_id = account_keys
+            .get(program_id_index as usize)
+            .ok_or("Instruction program index is out of range")?;
+        let accounts = account_indexes
+            .iter()
+            .map(|&index| account_keys.get(index as usize).cloned())
+            .collect::<Option<Vec<_>>>()
+            .ok_or("Instruction account index is out of range")?;
+        Ok(DecodedInstruction {
+            program_id: program_id.pubkey.clone(),
+            accounts,
+            data,
+        })
+    }
+
+    /// Fetches address lookup table accounts and returns their addresses keyed by table.
+    pub fn fetch_address_lookup_tables(
+        connection: &RpcClient,
+        table_keys: &[Pubkey],
+    ) -> Result<LookupTables, Box<dyn Error>> {
+        let accounts = connection.get_multiple_accounts(table_keys)?;
+
+        let mut lookup_tables = LookupTables::new();
+        for (key, account) in table_keys.iter().zip(accounts) {
+            let account = account.ok_or_else(|| format!("Lookup table {} not found", key))?;
+            let table = AddressLookupTable::deserialize(&account.data)
+                .map_err(|e| format!("Failed to deserialize lookup table {}: {}", key, e))?;
+            lookup_tables.insert(*key, table.addresses.to_vec());
+        }
+        Ok(lookup_tables)
+    }
+
+    /// Parses lookup tables from a JSON object mapping each table address to its list of addresses.
+    pub fn parse_address_lookup_tables(json: &str) -> Result<LookupTables, Box<dyn Error>> {
+        let tables: HashMap<String, Vec<String>> = serde_json::from_str(json)?;
+
+        let mut lookup_tables = LookupTables::new();
+        for (key, addresses) in tables {
+            let addresses = addresses
+                .iter()
+                .map(|address| Pubkey::from_str(address))
+                .collect::<Result<Vec<_>, _>>()?;
+            lookup_tables.insert(Pubkey::from_str(&key)?, addresses);
+        }
+        Ok(lookup_tables)
+    }
+
+    // Helper to derive signer and writable flags for static account keys from the message header
+    fn decode_account_keys(header: &DecodedHeader, keys: &[Pubkey]) -> Vec<DecodedAccountKey> {
+        let num_signers = header.num_required_signatures as usize;
+        let num_writable_signers = num_signers.saturating_sub(header.num_readonly_signed_accounts as usize);
+        let num_writable_unsigned = keys
+            .len()
+            .saturating_sub(num_signers)
+            .saturating_sub(header.num_readonly_unsigned_accounts as usize);
+
+        keys.iter()
+            .enumerate()
+            .map(|(i, key)| DecodedAccountKey {
+                pubkey: key.to_string(),
+                signer: i < num_signers,
+                writable: if i < num_signers {
+                    i < num_writable_signers
+                } else {
+                    i - num_signers < num_writable_unsigned
+                },
+            })
+            .collect()
+    }
+
+
+    /// An instruction identified and decoded using its IDL definition.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct DecodedIdlInstruction {
+        pub name: String,
+        pub accounts: Vec<LabeledAccount>,
+        pub args: Value,
+    }
+
+    /// An instruction account labeled with its IDL name.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct LabeledAccount {
+        pub name: String,
+        pub pubkey: String,
+        pub signer: bool,
+        pub writable: bool,
+    }
+
+    impl fmt::Display for DecodedIdlInstruction {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            writeln!(f, "Instruction: {}", self.name)?;
+            for account in &self.accounts {
+                let key = DecodedAccountKey {
+                    pubkey: account.pubkey.clone(),
+                    signer: account.signer,
+                    writable: account.writable,
+                };
+                writeln!(f, "  {}: {}", account.name, key)?;
+            }
+            write!(f, "  Args: {}", self.args)
+        }
+    }
+
+    /// Calculates the Anchor discriminator for an instruction from its IDL name.
+    pub fn calculate_instruction_discriminator(instruction_name: &str) -> [u8; DISCRIMINATOR_LEN] {
+        hash_discriminator(&format!("global:{}", to_snake_case(instruction_name)))
+    }
+
+    /// Identifies and decodes a single instruction using the IDL's instruction definitions.
+    pub fn decode_instruction_with_idl(
+        idl: &str,
+        instruction: &DecodedInstruction,
+    ) -> Result<DecodedIdlInstruction, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json).unwrap_or_default();
+        let definition = find_instruction_by_discriminator(&idl_json, &instruction.data)?;
+        decode_idl_instruction(definition, instruction, &types_map)
+    }
+
+    /// Decodes every instruction in a message that targets the given program.
+    pub fn decode_idl_instructions(
+        idl: &str,
+        program_address: &str,
+        message: &DecodedMessage,
+    ) -> Result<IdlInstructionResults, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json).unwrap_or_default();
+
+        Ok(message
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, instruction)| instruction.program_id == program_address)
+            .map(|(i, instruction)| {
+                let decoded = find_instruction_by_discriminator(&idl_json, &instruction.data)
+                    .and_then(|definition| decode_idl_instruction(definition, instruction, &types_map))
+                    .map_err(|e| e.to_string());
+                (i, decoded)
+            })
+            .collect())
+    }
+
+    // Helper to find the IDL instruction whose discriminator prefixes the instruction data
+    fn find_instruction_by_discriminator<'a>(idl_json: &'a Value, data: &[u8]) -> Result<&'a Value, Box<dyn Error>> {
+        let instructions = idl_json
+            .get("instructions")
+            .and_then(|i| i.as_array())
+            .ok_or("IDL does not contain 'instructions' or it is not an array")?;
+        let discriminator = data
+            .get(..DISCRIMINATOR_LEN)
+            .ok_or("Instruction data is shorter than the discriminator")?;
+
+        instructions
+            .iter()
+            .find(|instruction| idl_discriminator(instruction, calculate_instruction_discriminator).is_some_and(|d| d == discriminator))
+            .ok_or_else(|| format!("No IDL instruction matches discriminator {:?}", discriminator).into())
+    }
+
+    // Helper to get an IDL item's discriminator, preferring explicit bytes over the hash of its name
+    fn idl_discriminator(item: &Value, hash_name: fn(&str) -> [u8; DISCRIMINATOR_LEN]) -> Option<Vec<u8>> {
+        if let Some(explicit) = item.get("discriminator").and_then(|d| d.as_array()) {
+            return explicit.iter().map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok())).collect();
+        }
+        let name = item.get("name")?.as_str()?;
+        Some(hash_name(name).to_vec())
+    }
+
+    // Helper to decode the args and label the accounts of a matched instruction
+    fn decode_idl_instruction(
+        definition: &Value,
+        instruction: &DecodedInstruction,
+        types_map: &HashMap<String, &Value>,
+    ) -> Result<DecodedIdlInstruction, Box<dyn Error>> {
+        let name = definition
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or("Instruction has no name")?;
+        let empty = Vec::new();
+        let args = definition.get("args").and_then(|a| a.as_array()).unwrap_or(&empty);
+        let (decoded_args, _) = decode_struct_fields(&instruction.data, DISCRIMINATOR_LEN, args, &mut TypeResolver::new(types_map))?;
+
+        let mut account_names = Vec::new();
+        collect_instruction_account_names("", definition, &mut account_names);
+        let accounts = instruction
+            .accounts
+            .iter()
+            .enumerate()
+            .map(|(i, account)| LabeledAccount {
+                name: account_names
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| format!("remaining[{}]", i - account_names.len())),
+                pubkey: account.pubkey.clone(),
+                signer: account.signer,
+                writable: account.writable,
+            })
+            .collect();
+
+        Ok(DecodedIdlInstruction {
+            name: name.to_string(),
+            accounts,
+            args: Value::Object(decoded_args),
+        })
+    }
+
+    // Helper to flatten instruction account names, prefixing accounts inside nested groups
+    fn collect_instruction_account_names(prefix: &str, definition: &Value, names: &mut Vec<String>) {
+        let Some(accounts) = definition.get("accounts").and_then(|a| a.as_array()) else {
+            return;
+        };
+        for account in accounts {
+            let name = account.get("name").and_then(|n| n.as_str()).unwrap_or("unknown");
+            let path = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            if account.get("accounts").is_some() {
+                collect_instruction_account_names(&path, account, names);
+            } else {
+                names.push(path);
+            }
+        }
+    }
+
+    // Helper to convert an IDL camelCase name into the snake_case name Anchor hashes
+    fn to_snake_case(name: &str) -> String {
+        let chars: Vec<char> = name.chars().collect();
+        let mut snake = String::with_capacity(name.len() + 4);
+        for (i, &c) in chars.iter().enumerate() {
+            if c.is_uppercase() && i > 0 {
+                let prev = chars[i - 1];
+                let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+                if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_is_lower) {
+                    snake.push('_');
+                }
+            }
+            snake.extend(c.to_lowercase());
+        }
+        snake
+    }
+
+    /// Builds an instruction for an IDL instruction from JSON args and accounts keyed by their IDL names.
+    pub fn build_instruction_from_idl(
+        idl: &str,
+        program_address: &str,
+        instruction_name: &str,
+        args: &Value,
+        accounts: &HashMap<String, Pubkey>,
+    ) -> Result<Instruction, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json).unwrap_or_default();
+        let program_id = Pubkey::from_str(program_address)?;
+        let definition = idl_json
+            .get("instructions")
+            .and_then(|i| i.as_array())
+            .ok_or("IDL does not contain 'instructions' or it is not an array")?
+            .iter()
+            .find(|instruction| {
+                instruction
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .is_some_and(|name| to_snake_case(name) == to_snake_case(instruction_name))
+            })
+            .ok_or_else(|| format!("Instruction not found in IDL: {}", instruction_name))?;
+
+        let mut data = idl_discriminator(definition, calculate_instruction_discriminator).ok_or("Instruction has no discriminator")?;
+        let empty = Vec::new();
+        for arg in definition.get("args").and_then(|a| a.as_array()).unwrap_or(&empty) {
+            let name = arg.get("name").and_then(|n| n.as_str()).ok_or("Argument has no name")?;
+            let arg_type = arg.get("type").ok_or("Argument has no type")?;
+            let value = args
+                .get(name)
+                .ok_or_else(|| format!("Missing argument: {}", name))?;
+            encode_idl_value(value, arg_type, &mut TypeResolver::new(&types_map), &mut data)
+                .map_err(|e| format!("Invalid argument {}: {}", name, e))?;
+        }
+
+        let mut account_metas = Vec::new();
+        collect_instruction_account_metas("", definition, accounts, &program_id, &mut account_metas)?;
+
+        Ok(Instruction {
+            program_id,
+            accounts: account_metas,
+            data,
+        })
+    }
+
+    // Helper to resolve an instruction's IDL accounts in order, following nested groups
+    fn collect_instruction_account_metas(
+        prefix: &str,
+        definition: &Value,
+        accounts: &HashMap<String, Pubkey>,
+        program_id: &Pubkey,
+        metas: &mut Vec<AccountMeta>,
+    ) -> Result<(), Box<dyn Error>> {
+        let Some(idl_accounts) = definition.get("accounts").and_then(|a| a.as_array()) else {
+            return Ok(());
+        };
+        for account in idl_accounts {
+            let name = account.get("name").and_then(|n| n.as_str()).unwrap_or("unknown");
+            let path = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            if account.get("accounts").is_some() {
+                collect_instruction_account_metas(&path, account, accounts, program_id, metas)?;
+                continue;
+            }
+
+            // Legacy IDLs use isMut/isSigner/isOptional, newer ones writable/signer/optional
+            let flag = |legacy: &str, current: &str| {
+                [legacy, current]
+                    .iter()
+                    .any(|key| account.get(*key).and_then(|v| v.as_bool()).unwrap_or(false))
+            };
+            let fixed_address = account
+                .get("address")
+                .and_then(|a| a.as_str())
+                .map(Pubkey::from_str)
+                .transpose()?;
+            let pubkey = match accounts.get(&path).copied().or(fixed_address) {
+                Some(pubkey) => pubkey,
+                // Anchor marks an omitted optional account by passing the program id in its place
+                None if flag("isOptional", "optional") => {
+                    metas.push(AccountMeta::new_readonly(*program_id, false));
+                    continue;
+                }
+                None => return Err(format!("Missing account: {}", path).into()),
+            };
+            let signer = flag("isSigner", "signer");
+            metas.push(if flag("isMut", "writable") {
+                AccountMeta::new(pubkey, signer)
+            } else {
+                AccountMeta::new_readonly(pubkey, signer)
+            });
+        }
+        Ok(())
+    }
+
+    /// Compiles instructions into an unsigned legacy message and encodes it as base64.
+    pub fn encode_message(
+        instructions: &[Instruction],
+        payer: &Pubkey,
+        recent_blockhash: &Hash,
+    ) -> Result<String, Box<dyn Error>> {
+        let message = Message::new_with_blockhash(instructions, Some(payer), recent_blockhash);
+        Ok(BASE64_STANDARD.encode(bincode::serialize(&message)?))
+    }
+
+    /// The outcome of simulating a transaction, with the requested accounts as they would be afterwards.
+    #[derive(Debug, Clone)]
+    pub struct SimulatedTransaction {
+        pub slot: u64,
+        pub error: Option<Value>,
+        pub logs: Vec<String>,
+        pub units_consumed: Option<u64>,
+        pub accounts: Vec<(Pubkey, Option<Account>)>,
+    }
+
+    /// Simulates an unsigned base64 message against the latest blockhash and returns the requested post-simulation accounts.
+    pub fn simulate_message(
+        connection: &RpcClient,
+        message_data: &str,
+        account_addresses: &[Pubkey],
+    ) -> Result<SimulatedTransaction, Box<dyn Error>> {
+        let message = deserialize_message(message_data)?;
+        // Signature verification is skipped, so placeholder signatures only need the right count
+        let transaction = VersionedTransaction {
+            signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+            message,
+        };
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(connection.commitment()),
+            encoding: Some(UiTransactionEncoding::Base64),
+            accounts: (!account_addresses.is_empty()).then(|| RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: account_addresses.iter().map(|a| a.to_string()).collect(),
+            }),
+            ..RpcSimulateTransactionConfig::default()
+        };
+
+        let response = connection.simulate_transaction_with_config(&transaction, config)?;
+        let result = response.value;
+        let accounts = account_addresses
+            .iter()
+            .zip(result.accounts.unwrap_or_default())
+            .map(|(address, account)| (*address, account.and_then(|a| a.decode::<Account>())))
+            .collect();
+
+        Ok(SimulatedTransaction {
+            slot: response.context.slot,
+            error: result.err.map(serde_json::to_value).transpose()?,
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+            accounts,
+        })
+    }
+
+    /// Derives a PDA from the seeds an IDL instruction declares for the named account.
+    pub fn derive_pda_from_idl(
+        idl: &str,
+        program_address: &str,
+        account_name: &str,
+        seed_values: &HashMap<String, String>,
+        connection: Option<&RpcClient>,
+    ) -> Result<(Pubkey, u8), Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json).unwrap_or_default();
+        let (instruction, pda) = find_pda_definition(&idl_json, account_name)?;
+        let seeds = pda
+            .get("seeds")
+            .and_then(|s| s.as_array())
+            .ok_or("PDA definition has no seeds")?;
+
+        let seed_bytes = seeds
+            .iter()
+            .map(|seed| resolve_pda_seed(idl, seed, instruction, &types_map, seed_values, connection))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The PDA may belong to another program, given as a seed in the same format
+        let program_id = match pda.get("program").or_else(|| pda.get("programId")) {
+            Some(program) => {
+                let program_bytes = resolve_pda_seed(idl, program, instruction, &types_map, seed_values, connection)?;
+                Pubkey::try_from(program_bytes.as_slice()).map_err(|_| "PDA program is not a valid pubkey")?
+            }
+            None => Pubkey::from_str(program_address)?,
+        };
+
+        let seed_slices: Vec<&[u8]> = seed_bytes.iter().map(Vec::as_slice).collect();
+        Ok(Pubkey::find_program_address(&seed_slices, &program_id))
+    }
+
+    // Helper to find the first instruction declaring PDA seeds for an account, with the seeds themselves
+    fn find_pda_definition<'a>(idl_json: &'a Value, account_name: &str) -> Result<(&'a Value, &'a Value), Box<dyn Error>> {
+        let target = to_snake_case(account_name);
+        idl_json
+            .get("instructions")
+            .and_then(|i| i.as_array())
+            .into_iter()
+            .flatten()
+            .find_map(|instruction| find_pda_in_accounts(instruction, &target).map(|pda| (instruction, pda)))
+            .ok_or_else(|| format!("No PDA seeds found for account: {}", account_name).into())
+    }
+
+    // Helper to search an instruction's accounts, including nested groups, for a PDA definition
+    fn find_pda_in_accounts<'a>(definition: &'a Value, target: &str) -> Option<&'a Value> {
+        definition.get("accounts")?.as_array()?.iter().find_map(|account| {
+            if account.get("accounts").is_some() {
+                return find_pda_in_accounts(account, target);
+            }
+            let name = account.get("name")?.as_str()?;
+            (to_snake_case(name) == target).then(|| account.get("pda")).flatten()
+        })
+    }
+
+    // Helper to turn one IDL seed into bytes: a constant, an instruction arg, an account or an account's field
+    fn resolve_pda_seed(
+        idl: &str,
+        seed: &Value,
+        instruction: &Value,
+        types_map: &HashMap<String, &Value>,
+        seed_values: &HashMap<String, String>,
+        connection: Option<&RpcClient>,
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        let kind = seed.get("kind").and_then(|k| k.as_str()).ok_or("Seed has no kind")?;
+        if kind == "const" {
+            let value = seed.get("value").ok_or("Const seed has no value")?;
+            return match (value, seed.get("type")) {
+                (Value::Array(_), _) => Ok(serde_json::from_value(value.clone())?),
+                (_, Some(seed_type)) => encode_seed_value(value, seed_type, types_map),
+                (Value::String(s), None) => Ok(s.as_bytes().to_vec()),
+                _ => Err(format!("Unsupported const seed: {}", value).into()),
+            };
+        }
+
+        let path = seed.get("path").and_then(|p| p.as_str()).ok_or("Seed has no path")?;
+        // Newer IDLs omit seed types, so they are looked up from the arg or account definition
+        let seed_type = match (seed.get("type"), kind) {
+            (Some(seed_type), _) => seed_type.clone(),
+            (None, "arg") => arg_path_type(instruction, path, types_map)?,
+            (None, "account") => match path.split_once('.') {
+                Some((_, field)) => {
+                    let account_type = seed
+                        .get("account")
+                        .and_then(|a| a.as_str())
+                        .ok_or_else(|| format!("Seed {} does not name its account type", path))?;
+                    get_account_field_paths(idl, account_type)?
+                        .into_iter()
+                        .find(|(field_path, _)| field_path == field)
+                        .map(|(_, field_type)| field_type)
+                        .ok_or_else(|| format!("Field '{}' not found in {}", field, account_type))?
+                }
+                None => Value::String("publicKey".to_string()),
+            },
+            _ => return Err(format!("Unsupported seed kind: {}", kind).into()),
+        };
+
+        if let Some(input) = seed_values.get(path) {
+            // Strings and keys are taken as given, other values may be JSON such as numbers or enum variants
+            let value = match seed_type.as_str() {
+                Some("string" | "publicKey" | "pubkey") => Value::String(input.clone()),
+                _ => serde_json::from_str(input).unwrap_or_else(|_| Value::String(input.clone())),
+            };
+            return encode_seed_value(&value, &seed_type, types_map);
+        }
+
+        // An account field seed can be read from the account itself when only its address is given
+        if let (Some((base, field)), Some(connection), Some(account_type)) = (
+            path.split_once('.'),
+            connection,
+            seed.get("account").and_then(|a| a.as_str()),
+        ) {
+            if let Some(base_address) = seed_values.get(base) {
+                let data = connection.get_account_data(&Pubkey::from_str(base_address)?)?;
+                let decoded = decode_account_data(idl, account_type, &data)?;
+                let value = decoded
+                    .pointer(&format!("/{}", field.replace('.', "/")))
+                    .ok_or_else(|| format!("Field '{}' not found in {}", field, account_type))?;
+                return encode_seed_value(value, &seed_type, types_map);
+            }
+        }
+
+        Err(format!("Missing seed value: {}", path).into())
+    }
+
+    // Helper to resolve the type of an instruction arg or one of its nested fields
+    fn arg_path_type(
+        instruction: &Value,
+        path: &str,
+        types_map: &HashMap<String, &Value>,
+    ) -> Result<Value, Box<dyn Error>> {
+        let mut parts = path.split('.');
+        let arg_name = parts.next().ok_or("Seed path is empty")?;
+        let mut current = instruction
+            .get("args")
+            .and_then(|a| a.as_array())
+            .into_iter()
+            .flatten()
+            .find(|arg| arg.get("name").and_then(|n| n.as_str()) == Some(arg_name))
+            .and_then(|arg| arg.get("type"))
+            .ok_or_else(|| format!("Unknown instruction arg: {}", arg_name))?
+            .clone();
+        for part in parts {
+            current = resolve_nested_fields(&current, types_map)?
+                .iter()
+                .find(|field| field.get("name").and_then(|n| n.as_str()) == Some(part))
+                .and_then(|field| field.get("type"))
+                .ok_or_else(|| format!("Field '{}' not found in arg {}", part, arg_name))?
+                .clone();
+        }
+        Ok(current)
+    }
+
+    // Helper to encode a seed the way Anchor passes it: raw bytes for strings and bytes, Borsh otherwise
+    fn encode_seed_value(
+        value: &Value,
+        seed_type: &Value,
+        types_map: &HashMap<String, &Value>,
+    ) -> Result<Vec<u8>, Box<dyn Error>> {
+        let mut encoded = Vec::new();
+        encode_idl_value(value, seed_type, &mut TypeResolver::new(types_map), &mut encoded)?;
+        if matches!(seed_type.as_str(), Some("string" | "bytes")) {
+            encoded.drain(..4);
+        }
+        Ok(encoded)
+    }
+
+
+    /// An Anchor event decoded from a `Program data:` log line.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct DecodedEvent {
+        pub name: String,
+        pub program_id: Option<String>,
+        pub data: Value,
+    }
+
+    impl fmt::Display for DecodedEvent {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match &self.program_id {
+                Some(program_id) => write!(f, "{} (program {}): {}", self.name, program_id, self.data),
+                None => write!(f, "{}: {}", self.name, self.data),
+            }
+        }
+    }
+
+    /// Calculates the Anchor discriminator for an event.
+    pub fn calculate_event_discriminator(event_name: &str) -> [u8; DISCRIMINATOR_LEN] {
+        hash_discriminator(&format!("event:{}", event_name))
+    }
+
+    /// Decodes every IDL event emitted in a transaction's log messages, keeping a result per event.
+    pub fn decode_events(idl: &str, logs: &[String]) -> Result<EventResults, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json).unwrap_or_default();
+        let events = idl_json
+            .get("events")
+            .and_then(|e| e.as_array())
+            .ok_or("IDL does not contain 'events' or it is not an array")?;
+
+        let mut decoded_events = Vec::new();
+        let mut program_stack: Vec<String> = Vec::new();
+        for log in logs {
+            // Track the executing program so each event can be attributed to its emitter
+            if let Some(rest) = log.strip_prefix("Program ") {
+                let mut parts = rest.split_whitespace();
+                match (parts.next(), parts.next()) {
+                    (Some(program_id), Some("invoke")) => program_stack.push(program_id.to_string()),
+                    (Some(_), Some("success" | "failed:")) => {
+                        program_stack.pop();
+                    }
+                    _ => {}
+                }
+            }
+
+            let Some(encoded) = log.strip_prefix("Program data: ") else {
+                continue;
+            };
+            let Ok(data) = BASE64_STANDARD.decode(encoded.trim()) else {
+                continue;
+            };
+            let Some(discriminator) = data.get(..DISCRIMINATOR_LEN) else {
+                continue;
+            };
+
+            let Some(event) = events
+                .iter()
+                .find(|event| idl_discriminator(event, calculate_event_discriminator).is_some_and(|d| d == discriminator))
+            else {
+                continue;
+            };
+            let program_id = program_stack.last().cloned();
+            decoded_events.push(decode_event(event, &data, &types_map).map(|(name, data)| DecodedEvent {
+                name,
+                program_id,
+                data,
+            }));
+        }
+
+        Ok(decoded_events)
+    }
+
+    // Helper to decode one event's data into its name and fields
+    fn decode_event(event: &Value, data: &[u8], types_map: &HashMap<String, &Value>) -> Result<(String, Value), String> {
+        let name = event
+            .get("name")
+            .and_then(|n| n.as_str())
+            .ok_or("Event has no name")?;
+
+        // Legacy IDLs list event fields inline, newer ones define them in 'types'
+        let decoded = match event.get("fields").and_then(|f| f.as_array()) {
+            Some(fields) => decode_struct_fields(data, DISCRIMINATOR_LEN, fields, &mut TypeResolver::new(types_map))
+                .map(|(fields, _)| Value::Object(fields)),
+            None => match types_map.get(name) {
+                Some(event_type) => decode_custom_type(data, DISCRIMINATOR_LEN, event_type, &mut TypeResolver::new(types_map))
+                    .map(|(decoded, _)| decoded),
+                None => Err(format!("Unknown event type: {}", name).into()),
+            },
+        };
+        decoded
+            .map(|decoded| (name.to_string(), decoded))
+            .map_err(|e| format!("Failed to decode event {}: {}", name, e))
+    }
+
+    /// Fetches the log messages of a confirmed transaction.
+    pub fn fetch_transaction_logs(connection: &RpcClient, signature: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let transaction = get_confirmed_transaction(connection, signature)?;
+        let meta = transaction.transaction.meta.ok_or("Transaction has no status metadata")?;
+        Option::<Vec<String>>::from(meta.log_messages).ok_or_else(|| "Transaction has no log messages".into())
+    }
+
+    // Anchor framework error codes, names and messages (anchor_lang::error::ErrorCode)
+    const ANCHOR_ERRORS: &[(u32, &str, &str)] = &[
+        (100, "InstructionMissing", "8 byte instruction identifier not provided"),
+        (101, "InstructionFallbackNotFound", "Fallback functions are not supported"),
+        (102, "InstructionDidNotDeserialize", "The program could not deserialize the given instruction"),
+        (103, "InstructionDidNotSerialize", "The program could not serialize the given instruction"),
+        (1000, "IdlInstructionStub", "The program was compiled without idl instructions"),
+        (1001, "IdlInstructionInvalidProgram", "Invalid program given to the IDL instruction"),
+        (1002, "IdlAccountNotEmpty", "IDL account must be empty in order to resize, try closing first"),
+        (1500, "EventInstructionStub", "The program was compiled without `event-cpi` feature"),
+        (2000, "ConstraintMut", "A mut constraint was violated"),
+        (2001, "ConstraintHasOne", "A has one constraint was violated"),
+        (2002, "ConstraintSigner", "A signer constraint was violated"),
+        (2003, "ConstraintRaw", "A raw constraint was violated"),
+        (2004, "ConstraintOwner", "An owner constraint was violated"),
+        (2005, "ConstraintRentExempt", "A rent exemption constraint was violated"),
+        (2006, "ConstraintSeeds", "A seeds constraint was violated"),
+        (2007, "ConstraintExecutable", "An executable constraint was violated"),
+        (2008, "ConstraintState", "Deprecated Error, feel free to replace with something else"),
+        (2009, "ConstraintAssociated", "An associated constraint was violated"),
+        (2010, "ConstraintAssociatedInit", "An associated init constraint was violated"),
+        (2011, "ConstraintClose", "A close constraint was violated"),
+        (2012, "ConstraintAddress", "An address constraint was violated"),
+        (2013, "ConstraintZero", "Expected zero account discriminant"),
+        (2014, "ConstraintTokenMint", "A token mint constraint was violated"),
+        (2015, "ConstraintTokenOwner", "A token owner constraint was violated"),
+        (2016, "ConstraintMintMintAuthority", "A mint mint authority constraint was violated"),
+        (2017, "ConstraintMintFreezeAuthority", "A mint freeze authority constraint was violated"),
+        (2018, "ConstraintMintDecimals", "A mint decimals constraint was violated"),
+        (2019, "ConstraintSpace", "A space constraint was violated"),
+        (2020, "ConstraintAccountIsNone", "A required account for the constraint is None"),
+        (2021, "ConstraintTokenTokenProgram", "A token account token program constraint was violated"),
+        (2022, "ConstraintMintTokenProgram", "A mint token program constraint was violated"),
+        (
+            2023,
+            "ConstraintAssociatedTokenTokenProgram",
+            "An associated token account token program constraint was violated",
+        ),
+        (2500, "RequireViolated", "A require expression was violated"),
+        (2501, "RequireEqViolated", "A require_eq expression was violated"),
+        (2502, "RequireKeysEqViolated", "A require_keys_eq expression was violated"),
+        (2503, "RequireNeqViolated", "A require_neq expression was violated"),
+        (2504, "RequireKeysNeqViolated", "A require_keys_neq expression was violated"),
+        (2505, "RequireGtViolated", "A require_gt expression was violated"),
+        (2506, "RequireGteViolated", "A require_gte expression was violated"),
+        (3000, "AccountDiscriminatorAlreadySet", "The account discriminator was already set on this account"),
+        (3001, "AccountDiscriminatorNotFound", "No 8 byte discriminator was found on the account"),
+        (3002, "AccountDiscriminatorMismatch", "8 byte discriminator did not match what was expected"),
+        (3003, "AccountDidNotDeserialize", "Failed to deserialize the account"),
+        (3004, "AccountDidNotSerialize", "Failed to serialize the account"),
+        (3005, "AccountNotEnoughKeys", "Not enough account keys given to the instruction"),
+        (3006, "AccountNotMutable", "The given account is not mutable"),
+        (3007, "AccountOwnedByWrongProgram", "The given account is owned by a different program than expected"),
+        (3008, "InvalidProgramId", "Program ID was not as expected"),
+        (3009, "InvalidProgramExecutable", "Program account is not executable"),
+        (3010, "AccountNotSigner", "The given account did not sign"),
+        (3011, "AccountNotSystemOwned", "The given account is not owned by the system program"),
+        (3012, "AccountNotInitialized", "The program expected this account to be already initialized"),
+        (3013, "AccountNotProgramData", "The given account is not a program data account"),
+        (3014, "AccountNotAssociatedTokenAccount", "The given account is not the associated token account"),
+        (3015, "AccountSysvarMismatch", "The given public key does not match the required sysvar"),
+        (3016, "AccountReallocExceedsLimit", "The account reallocation exceeds the MAX_PERMITTED_DATA_INCREASE limit"),
+        (3017, "AccountDuplicateReallocs", "The account was duplicated for more than one reallocation"),
+        (4100, "DeclaredProgramIdMismatch", "The declared program id does not match the actual program id"),
+        (4101, "TryingToInitPayerAsProgramAccount", "You cannot/should not initialize the payer account as a program account"),
+        (4102, "InvalidNumericConversion", "Error during numeric conversion"),
+        (5000, "Deprecated", "The API being used is deprecated and should no longer be used"),
+    ];
+
+    // First error code available to programs, everything below is reserved by Anchor
+    const ANCHOR_USER_ERROR_START: u32 = 6000;
+
+    /// Where an explained error code was defined.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum ErrorSource {
+        Idl,
+        Anchor,
+    }
+
+    /// A program error code mapped to its name and message.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct ExplainedError {
+        pub code: u32,
+        pub name: String,
+        pub message: Option<String>,
+        pub source: ErrorSource,
+    }
+
+    impl fmt::Display for ExplainedError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let source = match self.source {
+                ErrorSource::Idl => "IDL",
+                ErrorSource::Anchor => "Anchor",
+            };
+            write!(f, "{} ({} / {:#x}, {})", self.name, self.code, self.code, source)?;
+            if let Some(message) = &self.message {
+                write!(f, ": {}", message)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Maps a custom program error code to the IDL's errors or Anchor's built-in errors.
+    pub fn explain_error_code(idl: Option<&str>, code: u32) -> Result<Option<ExplainedError>, Box<dyn Error>> {
+        if let Some(idl) = idl {
+            let idl_json = parse_idl(idl)?;
+            let idl_error = idl_json
+                .get("errors")
+                .and_then(|e| e.as_array())
+                .into_iter()
+                .flatten()
+                .find(|e| e.get("code").and_then(|c| c.as_u64()) == Some(code as u64));
+            if let Some(idl_error) = idl_error {
+                let name = idl_error
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .ok_or("IDL error has no name")?;
+                return Ok(Some(ExplainedError {
+                    code,
+                    name: name.to_string(),
+                    message: idl_error.get("msg").and_then(|m| m.as_str()).map(String::from),
+                    source: ErrorSource::Idl,
+                }));
+            }
+        }
+
+        if code >= ANCHOR_USER_ERROR_START {
+            return Ok(None);
+        }
+        Ok(ANCHOR_ERRORS
+            .iter()
+            .find(|(anchor_code, _, _)| *anchor_code == code)
+            .map(|(_, name, message)| ExplainedError {
+                code,
+                name: name.to_string(),
+                message: Some(message.to_string()),
+                source: ErrorSource::Anchor,
+            }))
+    }
+
+    /// Extracts a custom program error code from a code, an error log line or a transaction error JSON.
+    pub fn parse_error_code(input: &str) -> Result<u32, Box<dyn Error>> {
+        let input = input.trim();
+        if let Ok(json) = serde_json::from_str::<Value>(input) {
+            if !json.is_number() {
+                return find_custom_error_code(&json)
+                    .ok_or_else(|| "No custom program error found in transaction error".into());
+            }
+        }
+
+        // Accept log output such as "custom program error: 0x1771"
+        let code = input
+            .rsplit_once("custom program error:")
+            .map_or(input, |(_, code)| code)
+            .trim();
+        let parsed = match code.strip_prefix("0x").or_else(|| code.strip_prefix("0X")) {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => code.parse(),
+        };
+        parsed.map_err(|_| format!("Invalid error code: {}", code).into())
+    }
+
+    // Helper to find the code of an InstructionError's Custom variant anywhere in a JSON value
+    fn find_custom_error_code(json: &Value) -> Option<u32> {
+        match json {
+            Value::Object(map) => map
+                .get("Custom")
+                .and_then(|c| c.as_u64())
+                .and_then(|c| u32::try_from(c).ok())
+                .or_else(|| map.values().find_map(find_custom_error_code)),
+            Value::Array(items) => items.iter().find_map(find_custom_error_code),
+            _ => None,
+        }
+    }
+
+    /// A confirmed transaction with its decoded message and execution metadata.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct DecodedTransaction {
+        pub signature: String,
+        pub slot: u64,
+        pub block_time: Option<i64>,
+        pub fee: u64,
+        pub error: Option<Value>,
+        pub compute_units_consumed: Option<u64>,
+        pub message: DecodedMessage,
+        pub inner_instructions: Vec<DecodedInnerInstructions>,
+        pub token_balance_changes: Vec<TokenBalanceChange>,
+        pub logs: Vec<String>,
+    }
+
+    /// Instructions invoked through CPI while executing one top-level instruction.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct DecodedInnerInstructions {
+        pub index: u8,
+        pub instructions: Vec<DecodedInstruction>,
+    }
+
+    /// The change in a token account's balance, with amounts in UI units.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct TokenBalanceChange {
+        pub account: String,
+        pub mint: String,
+        pub owner: Option<String>,
+        pub decimals: u8,
+        pub pre_amount: String,
+        pub post_amount: String,
+        pub change: String,
+    }
+
+    impl fmt::Display for TokenBalanceChange {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{} (mint {}", self.account, self.mint)?;
+            if let Some(owner) = &self.owner {
+                write!(f, ", owner {}", owner)?;
+            }
+            write!(f, "): {} -> {} ({})", self.pre_amount, self.post_amount, self.change)
+        }
+    }
+
+    impl fmt::Display for DecodedTransaction {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            writeln!(f, "Signature: {}", self.signature)?;
+            match self.block_time {
+                Some(block_time) => writeln!(f, "Slot: {} (block time {})", self.slot, block_time)?,
+                None => writeln!(f, "Slot: {}", self.slot)?,
+            }
+            match &self.error {
+                Some(error) => writeln!(f, "Status: Failed {}", error)?,
+                None => writeln!(f, "Status: Success")?,
+            }
+            writeln!(f, "Fee: {} lamports", self.fee)?;
+            if let Some(units) = self.compute_units_consumed {
+                writeln!(f, "Compute units: {}", units)?;
+            }
+            write!(f, "{}", self.message)?;
+            if !self.inner_instructions.is_empty() {
+                writeln!(f, "Inner instructions:")?;
+                for inner in &self.inner_instructions {
+                    for (i, instruction) in inner.instructions.iter().enumerate() {
+                        writeln!(f, "  [{}.{}] Program: {}", inner.index, i, instruction.program_id)?;
+                        for account in &instruction.accounts {
+                            writeln!(f, "      Account: {}", account)?;
+                        }
+                        writeln!(f, "      Data: {}", BASE64_STANDARD.encode(&instruction.data))?;
+                    }
+                }
+            }
+            if !self.token_balance_changes.is_empty() {
+                writeln!(f, "Token balance changes:")?;
+                for change in &self.token_balance_changes {
+                    writeln!(f, "  {}", change)?;
+                }
+            }
+            writeln!(f, "Logs:")?;
+            for log in &self.logs {
+                writeln!(f, "  {}", log)?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Fetches a confirmed transaction and decodes its message, inner instructions and token balance changes.
+    pub fn fetch_transaction(connection: &RpcClient, signature: &str) -> Result<DecodedTransaction, Box<dyn Error>> {
+        let confirmed = get_confirmed_transaction(connection, signature)?;
+        let transaction = confirmed
+            .transaction
+            .transaction
+            .decode()
+            .ok_or("Failed to decode transaction")?;
+        let meta = confirmed.transaction.meta.ok_or("Transaction has no status metadata")?;
+
+        // The runtime reports the addresses it loaded, so lookups resolve without fetching the tables
+        let lookup_tables = match Option::<UiLoadedAddresses>::from(meta.loaded_addresses) {
+            Some(loaded) => lookup_tables_from_loaded_addresses(&transaction.message, &loaded)?,
+            None => LookupTables::new(),
+        };
+        let message = decode_versioned_message(&transaction.message, &lookup_tables)?;
+
+        let mut inner_instructions = Vec::new();
+        for inner in Option::<Vec<UiInnerInstructions>>::from(meta.inner_instructions).unwrap_or_default() {
+            let mut instructions = Vec::with_capacity(inner.instructions.len());
+            for instruction in inner.instructions {
+                let UiInstruction::Compiled(compiled) = instruction else {
+                    return Err("Inner instruction is not in compiled form".into());
+                };
+                let data = bs58::decode(&compiled.data).into_vec()?;
+                instructions.push(resolve_instruction(
+                    &message.account_keys,
+                    compiled.program_id_index,
+                    &compiled.accounts,
+                    data,
+                )?);
+            }
+            inner_instructions.push(DecodedInnerInstructions {
+                index: inner.index,
+                instructions,
+            });
+        }
+
+        let token_balance_changes = collect_token_balance_changes(
+            &message.account_keys,
+            Option::<Vec<UiTransactionTokenBalance>>::from(meta.pre_token_balances).unwrap_or_default(),
+            Option::<Vec<UiTransactionTokenBalance>>::from(meta.post_token_balances).unwrap_or_default(),
+        );
+
+        Ok(DecodedTransaction {
+            signature: signature.to_string(),
+            slot: confirmed.slot,
+            block_time: confirmed.block_time,
+            fee: meta.fee,
+            error: meta.err.map(serde_json::to_value).transpose()?,
+            compute_units_consumed: meta.compute_units_consumed.into(),
+            message,
+            inner_instructions,
+            token_balance_changes,
+            logs: Option::<Vec<String>>::from(meta.log_messages).unwrap_or_default(),
+        })
+    }
+
+    // Helper to fetch a confirmed transaction in binary form, accepting v0 transactions
+    fn get_confirmed_transaction(
+        connection: &RpcClient,
+        signature: &str,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, Box<dyn Error>> {
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(connection.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        Ok(connection.get_transaction_with_config(&Signature::from_str(signature)?, config)?)
+    }
+
+    // Rebuild sparse lookup tables from the loaded addresses, which list all writable lookups first, then all readonly ones
+    fn lookup_tables_from_loaded_addresses(
+        message: &VersionedMessage,
+        loaded: &UiLoadedAddresses,
+    ) -> Result<LookupTables, Box<dyn Error>> {
+        let mut lookup_tables = LookupTables::new();
+        let mut writable = loaded.writable.iter();
+        let mut readonly = loaded.readonly.iter();
+        for lookup in message.address_table_lookups().unwrap_or_default() {
+            let table = lookup_tables.entry(lookup.account_key).or_default();
+            for (indexes, addresses) in [
+                (&lookup.writable_indexes, &mut writable),
+                (&lookup.readonly_indexes, &mut readonly),
+            ] {
+                for &index in indexes {
+                    let address = addresses
+                        .next()
+                        .ok_or("Loaded addresses do not match the message's lookups")?;
+                    let index = index as usize;
+                    if table.len() <= index {
+                        table.resize(index + 1, Pubkey::default());
+                    }
+                    table[index] = Pubkey::from_str(address)?;
+                }
+            }
+        }
+        Ok(lookup_tables)
+    }
+
+    // Helper to pair pre and post token balances by account and keep the ones that changed
+    fn collect_token_balance_changes(
+        account_keys: &[DecodedAccountKey],
+        pre_balances: Vec<UiTransactionTokenBalance>,
+        post_balances: Vec<UiTransactionTokenBalance>,
+    ) -> Vec<TokenBalanceChange> {
+        let mut balances: BTreeMap<u8, (Option<UiTransactionTokenBalance>, Option<UiTransactionTokenBalance>)> =
+            BTreeMap::new();
+        for balance in pre_balances {
+            let entry = balances.entry(balance.account_index).or_default();
+            entry.0 = Some(balance);
+        }
+        for balance in post_balances {
+            let entry = balances.entry(balance.account_index).or_default();
+            entry.1 = Some(balance);
+        }
+
+        balances
+            .into_iter()
+            .filter_map(|(account_index, (pre, post))| {
+                let balance = post.as_ref().or(pre.as_ref())?;
+                let raw_amount = |b: &Option<UiTransactionTokenBalance>| {
+                    b.as_ref()
+                        .and_then(|b| b.ui_token_amount.amount.parse::<i128>().ok())
+                        .unwrap_or(0)
+                };
+                let (pre_raw, post_raw) = (raw_amount(&pre), raw_amount(&post));
+                if pre_raw == post_raw {
+                    return None;
+                }
+
+                let decimals = balance.ui_token_amount.decimals;
+                let change = format_token_amount(post_raw - pre_raw, decimals);
+                Some(TokenBalanceChange {
+                    account: account_keys
+                        .get(account_index as usize)
+                        .map_or_else(|| format!("#{}", account_index), |key| key.pubkey.clone()),
+                    mint: balance.mint.clone(),
+                    owner: Option::<String>::from(balance.owner.clone()),
+                    decimals,
+                    pre_amount: format_token_amount(pre_raw, decimals),
+                    post_amount: format_token_amount(post_raw, decimals),
+                    change: if post_raw > pre_raw { format!("+{}", change) } else { change },
+                })
+            })
+            .collect()
+    }
+
+    // Format a raw token amount using the mint's decimals, trimming trailing zeros
+    fn format_token_amount(raw: i128, decimals: u8) -> String {
+        // No real mint has more decimals than a u128 can scale, so show such amounts in raw units
+        let Some(scale) = 10u128.checked_pow(decimals as u32) else {
+            return raw.to_string();
+        };
+        let whole = raw.unsigned_abs() / scale;
+        let fraction = format!("{:0width$}", raw.unsigned_abs() % scale, width = decimals as usize);
+        let fraction = fraction.trim_end_matches('0');
+        let sign = if raw < 0 { "-" } else { "" };
+        if fraction.is_empty() {
+            format!("{}{}", sign, whole)
+        } else {
+            format!("{}{}.{}", sign, whole, fraction)
+        }
+    }
+
+    // Maximum number of signatures returned by a single getSignaturesForAddress call
+    const SIGNATURES_PAGE_LIMIT: usize = 1000;
+
+    /// A transaction that touched one or more of the addresses whose history was requested.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct HistoryEntry {
+        pub signature: String,
+        pub slot: u64,
+        pub block_time: Option<i64>,
+        pub error: Option<Value>,
+        pub addresses: Vec<String>,
+    }
+
+    /// Fetches up to `limit` signatures for an address, newest first, paging backwards with `before`.
+    pub fn get_signatures_for_address_paginated(
+        connection: &RpcClient,
+        address: &Pubkey,
+        limit: usize,
+        before: Option<Signature>,
+        until: Option<Signature>,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, Box<dyn Error>> {
+        let mut signatures = Vec::new();
+        let mut before = before;
+        while signatures.len() < limit {
+            let config = GetConfirmedSignaturesForAddress2Config {
+                before,
+                until,
+                limit: Some((limit - signatures.len()).min(SIGNATURES_PAGE_LIMIT)),
+                commitment: Some(connection.commitment()),
+            };
+            let page = connection.get_signatures_for_address_with_config(address, config)?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(Signature::from_str(&last.signature)?);
+            signatures.extend(page);
+        }
+        Ok(signatures)
+    }
+
+    /// Fetches the signatures of every address with bounded concurrency and merges them into one timeline, newest first.
+    pub fn get_address_history(
+        connection: &RpcClient,
+        addresses: &[Pubkey],
+        limit: usize,
+        before: Option<Signature>,
+        until: Option<Signature>,
+        concurrency: usize,
+    ) -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
+        let results = map_concurrently(addresses, concurrency, |address| {
+            get_signatures_for_address_paginated(connection, address, limit, before, until)
+                .map_err(|e| format!("Failed to fetch signatures for {}: {}", address, e))
+        });
+
+        // A transaction touching several of the addresses appears once, listing all of them
+        let mut entries: Vec<HistoryEntry> = Vec::new();
+        let mut positions: HashMap<String, usize> = HashMap::new();
+        for (address, result) in addresses.iter().zip(results) {
+            for status in result? {
+                if let Some(&position) = positions.get(&status.signature) {
+                    entries[position].addresses.push(address.to_string());
+                    continue;
+                }
+                positions.insert(status.signature.clone(), entries.len());
+                entries.push(HistoryEntry {
+                    signature: status.signature,
+                    slot: status.slot,
+                    block_time: status.block_time,
+                    error: status.err.map(serde_json::to_value).transpose()?,
+                    addresses: vec![address.to_string()],
+                });
+            }
+        }
+
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.slot));
+        Ok(entries)
+    }
+
+    /// Fetches and decodes several transactions with bounded concurrency, keeping their order.
+    pub fn fetch_transactions(
+        connection: &RpcClient,
+        signatures: &[String],
+        concurrency: usize,
+    ) -> Vec<Result<DecodedTransaction, String>> {
+        map_concurrently(signatures, concurrency, |signature| {
+            fetch_transaction(connection, signature).map_err(|e| e.to_string())
+        })
+    }
+
+    // Helper to apply a function to every item on at most `concurrency` threads, returning results in input order
+    fn map_concurrently<T: Sync, R: Send>(items: &[T], concurrency: usize, f: impl Fn(&T) -> R + Sync) -> Vec<R> {
+        let next = AtomicUsize::new(0);
+        let results: Mutex<Vec<Option<R>>> = Mutex::new(items.iter().map(|_| None).collect());
+        thread::scope(|scope| {
+            for _ in 0..concurrency.clamp(1, items.len().max(1)) {
+                scope.spawn(|| loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(item) = items.get(index) else {
+                        break;
+                    };
+                    let result = f(item);
+                    results.lock().unwrap()[index] = Some(result);
+                });
+            }
+        });
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("Every item is processed"))
+            .collect()
+    }
+
+    // Find accounts by variable value
+    pub fn find_accounts_by_variable(
+        connection: &RpcClient,
+        program_address: &str,
+        discrim: &[u8],
+        variable_offset: usize,
+        variable_value: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>, Box<dyn Error>> {
+        let program_pubkey = Pubkey::from_str(program_address)?;
+
+        let discrim_filter = RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Bytes(discrim.into())));
+        let variable_filter = RpcFilterType::Memcmp(Memcmp::new(
+            variable_offset,
+            MemcmpEncodedBytes::Bytes(variable_value.into()),
+        ));
+
+        let config = create_program_account_config(vec![discrim_filter, variable_filter]);
+
+        let accounts = connection.get_program_accounts_with_config(&program_pubkey, config)?;
+        Ok(accounts)
+    }
+
+    // Calculate discriminator for an account
+    pub fn calculate_discriminator(account_name: &str) -> [u8; DISCRIMINATOR_LEN] {
+        hash_discriminator(&format!("account:{}", account_name))
+    }
+
+    // Helper to hash a namespaced name into an 8-byte discriminator
+    fn hash_discriminator(preimage: &str) -> [u8; DISCRIMINATOR_LEN] {
+        let mut hasher = Sha256::new();
+        hasher.update(preimage.as_bytes());
+        let hash = hasher.finalize();
+        let mut discriminator = [0u8; DISCRIMINATOR_LEN];
+        discriminator.copy_from_slice(&hash[..DISCRIMINATOR_LEN]);
+        discriminator
+    }
+
+    // Find accounts by criteria (comprehensive search)
+    pub fn find_accounts_by_criteria(
+        connection: &RpcClient,
+        idl: &str,
+        program_address: &str,
+        account_name: &str,
+        variable_path: &str,
+        variable_value: &[u8],
+    ) -> Result<Vec<(Pubkey, Account)>, Box<dyn Error>> {
+        // Look up the discriminator the IDL declares for the account
+        let discriminator = find_account_discriminator(idl, account_name)?;
+
+        // Get the offset of the variable
+        let variable_offset = get_variable_offset_from_idl(idl, account_name, variable_path)?;
+
+        // Encode the discriminator and variable value in Base64
+        let discriminator_base64 = BASE64_STANDARD.encode(discriminator);
+        let variable_value_base64 = BASE64_STANDARD.encode(variable_value);
+
+        // Fetch accounts matching the discriminator and variable value
+        let program_pubkey = Pubkey::from_str(&program_address)?;
+
+        let discrim_filter = RpcFilterType::Memcmp(
+            Memcmp::new(0, MemcmpEncodedBytes::Base64(discriminator_base64))
+        );
+        let variable_filter = RpcFilterType::Memcmp(
+            Memcmp::new(variable_offset, MemcmpEncodedBytes::Base64(variable_value_base64))
+        );
+
+        let config = create_program_account_config(vec![discrim_filter, variable_filter]);
+        let accounts = connection.get_program_accounts_with_config(&program_pubkey, config)?;
+        
+        Ok(accounts)
+    }
+
+    // IDL parsing and variable resolving functions
+    pub fn get_variable_offset_from_idl(
+        idl: &str,
+        account_name: &str,
+        variable_path: &str,
+    ) -> Result<usize, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json)?;
+        let account = find_account_definition(&idl_json, account_name)?;
+        let mut mode = account_layout_mode(&idl_json, &types_map, account_name)?;
+        let mut resolver = TypeResolver::for_layout(&types_map);
+
+        // Only the fields before the variable are sized, so anything after it cannot get in the way
+        let path_parts: Vec<&str> = variable_path.split('.').collect();
+        let mut offset = DISCRIMINATOR_LEN;
+        let mut current_fields = extract_account_fields(account)?;
+        for (depth, part) in path_parts.iter().enumerate() {
+            let mut found = None;
+            for field in current_fields {
+                let field_name = field
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .ok_or("Field has no name")?;
+                let field_type = field.get("type").ok_or("Field has no type")?;
+                let size_align = match mode {
+                    LayoutMode::Borsh if field_name == *part => Ok((0, 1)),
+                    LayoutMode::Borsh => resolver.field_size(field_type).map(|size| (size, 1)),
+                    _ => resolver.zero_copy_size_align(field_type, mode == LayoutMode::Packed),
+                };
+                let (size, align) = match size_align {
+                    Ok(size_align) => size_align,
+                    // The variable itself may be dynamically sized, it only needs to start at a known offset
+                    Err(e) if e.is::<DynamicSizeError>() && field_name == *part => (0, 1),
+                    Err(e) if e.is::<DynamicSizeError>() => {
+                        return Err(format!("Field '{}' follows a dynamically sized field", variable_path).into());
+                    }
+                    Err(e) => return Err(e),
+                };
+                offset = align_up(offset, align);
+                if field_name == *part {
+                    found = Some(field_type);
+                    break;
+                }
+                offset += size;
+            }
+            let field_type = found.ok_or_else(|| format!("Field '{}' not found", part))?;
+            if depth + 1 < path_parts.len() {
+                current_fields = resolve_nested_fields(field_type, &types_map)?;
+                // A packed nested struct stays packed even inside a repr(C) account
+                let nested_packed = type_repr_packed(types_map[defined_type_name(field_type).unwrap_or_default()]);
+                if mode == LayoutMode::ReprC && nested_packed {
+                    mode = LayoutMode::Packed;
+                }
+            }
+        }
+        Ok(offset)
+    }
+
+    /// How an account's fields are placed in its data.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum LayoutMode {
+        /// Borsh serialization, fields tightly packed one after another
+        Borsh,
+        /// Zero-copy `repr(C)` layout, fields aligned with padding between them
+        ReprC,
+        /// Zero-copy `repr(packed)` layout, fixed-size fields without padding
+        Packed,
+    }
+
+    impl fmt::Display for LayoutMode {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                LayoutMode::Borsh => write!(f, "borsh"),
+                LayoutMode::ReprC => write!(f, "repr-c"),
+                LayoutMode::Packed => write!(f, "packed"),
+            }
+        }
+    }
+
+    /// Position and size of one field in an account's data, including the discriminator in offsets.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct FieldLayout {
+        pub path: String,
+        pub field_type: String,
+        /// Unknown once an earlier field has a dynamic size
+        pub offset: Option<usize>,
+        /// Unknown for dynamically sized fields such as strings, vectors and options
+        pub size: Option<usize>,
+        /// Alignment of the field, always 1 outside `repr(C)` layouts
+        pub align: usize,
+    }
+
+    impl FieldLayout {
+        /// Returns whether the field has a fixed size.
+        pub fn is_fixed(&self) -> bool {
+            self.size.is_some()
+        }
+    }
+
+    /// Picks the layout mode from the IDL's serialization and repr attributes for the account.
+    pub fn detect_layout_mode(idl: &str, account_name: &str) -> Result<LayoutMode, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        account_layout_mode(&idl_json, &build_types_map(&idl_json)?, account_name)
+    }
+
+    // Helper to pick an account's layout mode from an already parsed IDL
+    fn account_layout_mode(
+        idl_json: &Value,
+        types_map: &HashMap<String, &Value>,
+        account_name: &str,
+    ) -> Result<LayoutMode, Box<dyn Error>> {
+        let account = find_account_definition(idl_json, account_name)?;
+
+        // Newer IDLs put these attributes on the type of the same name rather than the account
+        let definitions = [Some(account), types_map.get(account_name).copied()];
+        let mut zero_copy = false;
+        for definition in definitions.into_iter().flatten() {
+            if type_repr_packed(definition) {
+                return Ok(LayoutMode::Packed);
+            }
+            let serialization = definition.get("serialization").and_then(|s| s.as_str());
+            let repr_kind = definition.get("repr").and_then(|r| r.get("kind")).and_then(|k| k.as_str());
+            zero_copy |= matches!(serialization, Some("bytemuck" | "bytemuckunsafe")) || repr_kind == Some("c");
+        }
+        Ok(if zero_copy { LayoutMode::ReprC } else { LayoutMode::Borsh })
+    }
+
+    /// Lays out every field path of an account in the layout mode its IDL declares.
+    pub fn get_account_layout(idl: &str, account_name: &str) -> Result<Vec<FieldLayout>, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json)?;
+        let mode = account_layout_mode(&idl_json, &types_map, account_name)?;
+        account_field_layouts(&idl_json, &types_map, account_name, mode)
+    }
+
+    /// Lays out every field path of an account in the given mode, nested struct fields following their parent.
+    pub fn get_account_layout_with_mode(
+        idl: &str,
+        account_name: &str,
+        mode: LayoutMode,
+    ) -> Result<Vec<FieldLayout>, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        account_field_layouts(&idl_json, &build_types_map(&idl_json)?, account_name, mode)
+    }
+
+    // Helper to lay out an account from an already parsed IDL
+    fn account_field_layouts(
+        idl_json: &Value,
+        types_map: &HashMap<String, &Value>,
+        account_name: &str,
+        mode: LayoutMode,
+    ) -> Result<Vec<FieldLayout>, Box<dyn Error>> {
+        let account = find_account_definition(idl_json, account_name)?;
+
+        let mut layout = Vec::new();
+        collect_field_layouts(
+            "",
+            extract_account_fields(account)?,
+            Some(DISCRIMINATOR_LEN),
+            mode,
+            &mut TypeResolver::for_layout(types_map),
+            &mut layout,
+        )?;
+        Ok(layout)
+    }
+
+    /// Returns the total size of an account layout including the discriminator and any trailing padding, if it is fixed.
+    pub fn account_layout_size(layout: &[FieldLayout]) -> Option<usize> {
+        let top_level: Vec<&FieldLayout> = layout.iter().filter(|field| !field.path.contains('.')).collect();
+        let Some(last) = top_level.last() else {
+            return Some(DISCRIMINATOR_LEN);
+        };
+        let end = last.offset? + last.size?;
+        let align = top_level.iter().map(|field| field.align).max().unwrap_or(1);
+        Some(align_up(end, align))
+    }
+
+    // Helper to lay out struct fields from a starting offset, descending into nested structs
+    fn collect_field_layouts(
+        prefix: &str,
+        fields: &[Value],
+        mut offset: Option<usize>,
+        mode: LayoutMode,
+        resolver: &mut TypeResolver,
+        layout: &mut Vec<FieldLayout>,
+    ) -> Result<(), Box<dyn Error>> {
+        for field in fields {
+            let field_name = field
+                .get("name")
+                .and_then(|n| n.as_str())
+                .ok_or("Field has no name")?;
+            let field_type = field.get("type").ok_or("Field has no type")?;
+            let path = if prefix.is_empty() {
+                field_name.to_string()
+            } else {
+                format!("{}.{}", prefix, field_name)
+            };
+
+            // Types the IDL cannot size are errors, only sizes that depend on the data leave the field unsized
+            let size_align = match mode {
+                LayoutMode::Borsh => resolver.field_size(field_type).map(|size| (size, 1)),
+                _ => resolver.zero_copy_size_align(field_type, mode == LayoutMode::Packed),
+            };
+            let (size, align) = match size_align {
+                Ok((size, align)) => (Some(size), align),
+                Err(e) if e.is::<DynamicSizeError>() => (None, 1),
+                Err(e) => return Err(e),
+            };
+            let field_offset = offset.map(|offset| align_up(offset, align));
+            layout.push(FieldLayout {
+                path: path.clone(),
+                field_type: format_idl_type(field_type),
+                offset: field_offset,
+                size,
+                align,
+            });
+            if let Ok(nested_fields) = resolve_nested_fields(field_type, resolver.types_map) {
+                let type_name = defined_type_name(field_type).unwrap_or_default();
+                // A packed nested struct stays packed even inside a repr(C) account
+                let nested_packed = type_repr_packed(resolver.enter(type_name)?);
+                let nested_mode = if mode == LayoutMode::ReprC && nested_packed { LayoutMode::Packed } else { mode };
+                let nested = collect_field_layouts(&path, nested_fields, field_offset, nested_mode, resolver, layout);
+                resolver.leave();
+                nested?;
+            }
+            offset = field_offset.zip(size).map(|(offset, size)| offset + size);
+        }
+        Ok(())
+    }
+
+    // Helper to check whether a type definition declares a packed repr
+    fn type_repr_packed(definition: &Value) -> bool {
+        definition
+            .get("repr")
+            .and_then(|repr| repr.get("packed"))
+            .and_then(|packed| packed.as_bool())
+            .unwrap_or(false)
+    }
+
+    // Helper to round an offset up to a multiple of the alignment
+    fn align_up(offset: usize, align: usize) -> usize {
+        offset.div_ceil(align) * align
+    }
+
+    /// The first place where account data disagrees with the IDL layout.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct LayoutMismatch {
+        pub offset: usize,
+        pub path: String,
+        pub reason: String,
+    }
+
+    impl fmt::Display for LayoutMismatch {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "offset {} ({}): {}", self.offset, self.path, self.reason)
+        }
+    }
+
+    /// The outcome of checking one account's data against its IDL layout.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct LayoutValidation {
+        pub data_len: usize,
+        /// Layout size including the discriminator, when every field has a fixed size
+        pub layout_size: Option<usize>,
+        /// Bytes covered by the layout, when the data follows it to the end
+        pub checked_len: Option<usize>,
+        pub mismatch: Option<LayoutMismatch>,
+    }
+
+    /// Walks account data along its IDL layout, checking lengths, tags, bools and strings on the way.
+    pub fn validate_account_layout(
+        idl: &str,
+        account_name: &str,
+        data: &[u8],
+    ) -> Result<LayoutValidation, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json)?;
+        let account = find_account_definition(&idl_json, account_name)?;
+        let fields = extract_account_fields(account)?;
+        let discriminator =
+            lookup_account_discriminator(&idl_json, account_name).ok_or("Account has an invalid discriminator")?;
+        let mode = account_layout_mode(&idl_json, &types_map, account_name)?;
+        let layout = account_field_layouts(&idl_json, &types_map, account_name, mode)?;
+        let layout_size = account_layout_size(&layout);
+        // Zero-copy fields are checked at their fixed place, so every field needs one
+        let mut zero_copy_fields = Vec::new();
+        if mode != LayoutMode::Borsh {
+            for field in &layout {
+                let (Some(offset), Some(size)) = (field.offset, field.size) else {
+                    return Err(format!(
+                        "Zero-copy layout of {} cannot be resolved: {} ({}) has no fixed offset and size",
+                        account_name, field.path, field.field_type
+                    )
+                    .into());
+                };
+                zero_copy_fields.push((field, offset, size));
+            }
+        }
+
+        let mut validation = LayoutValidation {
+            data_len: data.len(),
+            layout_size,
+            checked_len: None,
+            mismatch: None,
+        };
+        if !data.starts_with(&discriminator) {
+            validation.mismatch = Some(LayoutMismatch {
+                offset: 0,
+                path: "(discriminator)".to_string(),
+                reason: format!("expected {}", to_hex(&discriminator)),
+            });
+            return Ok(validation);
+        }
+
+        let checked = match mode {
+            LayoutMode::Borsh => check_struct_fields(data, DISCRIMINATOR_LEN, fields, &mut TypeResolver::new(&types_map), "")
+                .map(|size| DISCRIMINATOR_LEN + size),
+            _ => check_zero_copy_fields(data, &zero_copy_fields, layout_size, &types_map),
+        };
+        match checked {
+            Ok(end) => {
+                validation.checked_len = Some(end);
+                // Accounts are often allocated with spare room, which is fine as long as it stays zeroed
+                if let Some(position) = data[end..].iter().position(|b| *b != 0) {
+                    validation.mismatch = Some(LayoutMismatch {
+                        offset: end + position,
+                        path: "(trailing data)".to_string(),
+                        reason: format!("{} bytes past the end of the layout are not zero-filled", data.len() - end),
+                    });
+                }
+            }
+            Err(mismatch) => validation.mismatch = Some(mismatch),
+        }
+        Ok(validation)
+    }
+
+    // Helper to check a zero-copy account field by field at its aligned offset, returning the bytes covered
+    fn check_zero_copy_fields(
+        data: &[u8],
+        fields: &[(&FieldLayout, usize, usize)],
+        layout_size: Option<usize>,
+        types_map: &HashMap<String, &Value>,
+    ) -> Result<usize, LayoutMismatch> {
+        for &(field, offset, size) in fields {
+            let mismatch = |offset: usize, reason: String| LayoutMismatch {
+                offset,
+                path: field.path.clone(),
+                reason,
+            };
+            let bytes = data.get(offset..offset + size).ok_or_else(|| {
+                mismatch(offset, format!("needs {} bytes but the data ends at {}", size, data.len()))
+            })?;
+            let variants = types_map
+                .get(&field.field_type)
+                .filter(|definition| definition["type"]["kind"] == "enum")
+                .and_then(|definition| definition["type"]["variants"].as_array());
+            match (field.field_type.as_str(), variants) {
+                ("bool", _) if bytes[0] > 1 => {
+                    return Err(mismatch(offset, format!("bool is {}, expected 0 or 1", bytes[0])));
+                }
+                (_, Some(variants)) if bytes[0] as usize >= variants.len() => {
+                    return Err(mismatch(
+                        offset,
+                        format!("enum tag is {}, but there are only {} variants", bytes[0], variants.len()),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        let size = layout_size.unwrap_or(DISCRIMINATOR_LEN);
+        if data.len() < size {
+            return Err(LayoutMismatch {
+                offset: data.len(),
+                path: "(padding)".to_string(),
+                reason: format!("needs {} bytes but the data ends at {}", size, data.len()),
+            });
+        }
+        Ok(size)
+    }
+
+    // Helper to check struct fields in order, returning the bytes they cover
+    fn check_struct_fields(
+        data: &[u8],
+        offset: usize,
+        fields: &[Value],
+        resolver: &mut TypeResolver,
+        prefix: &str,
+    ) -> Result<usize, LayoutMismatch> {
+        let mut size = 0;
+        for field in fields {
+            let name = field.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+            let path = if prefix.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}.{}", prefix, name)
+            };
+            let field_type = field.get("type").unwrap_or(&Value::Null);
+            size += check_idl_value(data, offset + size, field_type, resolver, &path)?;
+        }
+        Ok(size)
+    }
+
+    // Helper to check one value of an IDL type, returning the bytes it covers
+    fn check_idl_value(
+        data: &[u8],
+        offset: usize,
+        field_type: &Value,
+        resolver: &mut TypeResolver,
+        path: &str,
+    ) -> Result<usize, LayoutMismatch> {
+        let mismatch = |offset: usize, reason: String| LayoutMismatch {
+            offset,
+            path: path.to_string(),
+            reason,
+        };
+        let take = |offset: usize, len: usize| {
+            data.get(offset..offset + len).ok_or_else(|| {
+                mismatch(offset, format!("needs {} bytes but the data ends at {}", len, data.len()))
+            })
+        };
+        let read_u32 = |offset: usize| take(offset, 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize);
+
+        match field_type {
+            Value::String(type_str) => match type_str.as_str() {
+                "bool" => match take(offset, 1)?[0] {
+                    0 | 1 => Ok(1),
+                    byte => Err(mismatch(offset, format!("bool is {}, expected 0 or 1", byte))),
+                },
+                "string" => {
+                    let len = read_u32(offset)?;
+                    let bytes = take(offset + 4, len)?;
+                    std::str::from_utf8(bytes).map_err(|e| mismatch(offset + 4, format!("string is not UTF-8: {}", e)))?;
+                    Ok(4 + len)
+                }
+                "bytes" => {
+                    let len = read_u32(offset)?;
+                    take(offset + 4, len)?;
+                    Ok(4 + len)
+                }
+                primitive if !resolver.types_map.contains_key(primitive) => {
+                    let size = resolver
+                        .field_size(field_type)
+                        .map_err(|_| mismatch(offset, format!("unknown type {}", primitive)))?;
+                    take(offset, size)?;
+                    Ok(size)
+                }
+                custom_type => check_defined_type(data, offset, custom_type, resolver, path),
+            },
+            Value::Object(obj) if obj.get("array").is_some() => {
+                let item_type = &obj["array"][0];
+                let count = obj["array"][1].as_u64().unwrap_or(0) as usize;
+                let mut size = 0;
+                for i in 0..count {
+                    size += check_idl_value(data, offset + size, item_type, resolver, &format!("{}[{}]", path, i))?;
+                }
+                Ok(size)
+            }
+            Value::Object(obj) if obj.get("vec").is_some() => {
+                let count = read_u32(offset)?;
+                // A count the remaining bytes cannot hold is corrupt data rather than a long vector
+                let remaining = data.len().saturating_sub(offset + 4);
+                if count.saturating_mul(min_encoded_size(&obj["vec"], resolver)) > remaining {
+                    return Err(mismatch(offset, format!("vec length {} exceeds the {} remaining bytes", count, remaining)));
+                }
+                let mut size = 4;
+                for i in 0..count {
+                    size += check_idl_value(data, offset + size, &obj["vec"], resolver, &format!("{}[{}]", path, i))?;
+                }
+                Ok(size)
+            }
+            Value::Object(obj) if obj.get("option").is_some() => match take(offset, 1)?[0] {
+                0 => Ok(1),
+                1 => Ok(1 + check_idl_value(data, offset + 1, &obj["option"], resolver, path)?),
+                tag => Err(mismatch(offset, format!("option tag is {}, expected 0 or 1", tag))),
+            },
+            Value::Object(obj) if obj.get("coption").is_some() => match read_u32(offset)? {
+                0 => {
+                    let size = resolver
+                        .field_size(&obj["coption"])
+                        .map_err(|e| mismatch(offset + 4, e.to_string()))?;
+                    take(offset + 4, size)?;
+                    Ok(4 + size)
+                }
+                1 => Ok(4 + check_idl_value(data, offset + 4, &obj["coption"], resolver, path)?),
+                tag => Err(mismatch(offset, format!("coption tag is {}, expected 0 or 1", tag))),
+            },
+            Value::Object(obj) if obj.get("tuple").is_some() => {
+                let elements = obj["tuple"].as_array().map(Vec::as_slice).unwrap_or_default();
+                let mut size = 0;
+                for (i, element) in elements.iter().enumerate() {
+                    size += check_idl_value(data, offset + size, element, resolver, &format!("{}.{}", path, i))?;
+                }
+                Ok(size)
+            }
+            _ => match defined_type_name(field_type) {
+                Some(name) => check_defined_type(data, offset, name, resolver, path),
+                None => Err(mismatch(offset, format!("unsupported type {}", field_type))),
+            },
+        }
+    }
+
+    // Helper to check a named type from the IDL 'types' section, guarding against recursive definitions
+    fn check_defined_type(
+        data: &[u8],
+        offset: usize,
+        name: &str,
+        resolver: &mut TypeResolver,
+        path: &str,
+    ) -> Result<usize, LayoutMismatch> {
+        let custom_type_def = resolver.enter_at(name, Some(offset)).map_err(|e| LayoutMismatch {
+            offset,
+            path: path.to_string(),
+            reason: e.to_string(),
+        })?;
+        let checked = check_custom_type(data, offset, custom_type_def, resolver, path);
+        resolver.leave();
+        checked
+    }
+
+    // Helper to check a struct or enum from the IDL 'types' section
+    fn check_custom_type(
+        data: &[u8],
+        offset: usize,
+        custom_type_def: &Value,
+        resolver: &mut TypeResolver,
+        path: &str,
+    ) -> Result<usize, LayoutMismatch> {
+        let type_kind = &custom_type_def["type"];
+        let empty = Vec::new();
+        match type_kind["kind"].as_str() {
+            Some("struct") => {
+                let fields = type_kind["fields"].as_array().unwrap_or(&empty);
+                check_struct_fields(data, offset, fields, resolver, path)
+            }
+            Some("enum") => {
+                let variants = type_kind["variants"].as_array().unwrap_or(&empty);
+                let tag = *data.get(offset).ok_or_else(|| LayoutMismatch {
+                    offset,
+                    path: path.to_string(),
+                    reason: format!("needs 1 byte but the data ends at {}", data.len()),
+                })?;
+                let variant = variants.get(tag as usize).ok_or_else(|| LayoutMismatch {
+                    offset,
+                    path: path.to_string(),
+                    reason: format!("enum tag is {}, but there are only {} variants", tag, variants.len()),
+                })?;
+
+                let variant_path = format!("{}.{}", path, variant["name"].as_str().unwrap_or("?"));
+                let variant_fields = variant["fields"].as_array().unwrap_or(&empty);
+                if variant_fields.first().is_some_and(|field| field.get("name").is_some()) {
+                    return Ok(1 + check_struct_fields(data, offset + 1, variant_fields, resolver, &variant_path)?);
+                }
+                let mut size = 1;
+                for (i, field_type) in variant_fields.iter().enumerate() {
+                    size += check_idl_value(data, offset + size, field_type, resolver, &format!("{}.{}", variant_path, i))?;
+                }
+                Ok(size)
+            }
+            _ => Err(LayoutMismatch {
+                offset,
+                path: path.to_string(),
+                reason: "unsupported custom type kind".to_string(),
+            }),
+        }
+    }
+
+    // Helper to format an IDL type the way it would be written in Rust
+    fn format_idl_type(field_type: &Value) -> String {
+        if let Some(name) = field_type.as_str().or_else(|| defined_type_name(field_type)) {
+            return name.to_string();
+        }
+        let Some((kind, inner)) = field_type.as_object().and_then(|obj| obj.iter().next()) else {
+            return field_type.to_string();
+        };
+        match (kind.as_str(), inner) {
+            ("array", Value::Array(parts)) if parts.len() == 2 => {
+                format!("[{}; {}]", format_idl_type(&parts[0]), parts[1])
+            }
+            ("tuple", Value::Array(elements)) => {
+                let elements: Vec<String> = elements.iter().map(format_idl_type).collect();
+                format!("({})", elements.join(", "))
+            }
+            ("option", inner) => format!("Option<{}>", format_idl_type(inner)),
+            ("coption", inner) => format!("COption<{}>", format_idl_type(inner)),
+            ("vec", inner) => format!("Vec<{}>", format_idl_type(inner)),
+            _ => field_type.to_string(),
+        }
+    }
+
+    /// Lists the names of all account types defined in the IDL.
+    pub fn get_account_names_from_idl(idl: &str) -> Result<Vec<String>, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let accounts = idl_json
+            .get("accounts")
+            .and_then(|a| a.as_array())
+            .ok_or("IDL does not contain 'accounts' or it is not an array")?;
+
+        Ok(accounts
+            .iter()
+            .filter_map(|acc| acc.get("name")?.as_str().map(String::from))
+            .collect())
+    }
+
+    /// Finds the IDL account type whose discriminator matches the start of the account data.
+    pub fn detect_account_type(idl: &str, data: &[u8]) -> Result<Option<String>, Box<dyn Error>> {
+        detect_account_name(&parse_idl(idl)?, data)
+    }
+
+    // Helper to match data against the discriminators of an already parsed IDL
+    fn detect_account_name(idl_json: &Value, data: &[u8]) -> Result<Option<String>, Box<dyn Error>> {
+        let accounts = idl_json
+            .get("accounts")
+            .and_then(|a| a.as_array())
+            .ok_or("IDL does not contain 'accounts' or it is not an array")?;
+
+        Ok(accounts.iter().find_map(|account| {
+            let discriminator = idl_discriminator(account, calculate_discriminator)?;
+            data.starts_with(&discriminator)
+                .then(|| account.get("name")?.as_str().map(String::from))
+                .flatten()
+        }))
+    }
+
+    /// The kind of difference found between two versions of an IDL.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+    #[serde(rename_all = "kebab-case")]
+    pub enum IdlChangeKind {
+        AccountAdded,
+        AccountRemoved,
+        DiscriminatorChanged,
+        LayoutModeChanged,
+        SizeChanged,
+        FieldAdded,
+        FieldRemoved,
+        FieldsReordered,
+        TypeChanged,
+        OffsetChanged,
+        BecameDynamic,
+    }
+
+    impl fmt::Display for IdlChangeKind {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let kind = match self {
+                IdlChangeKind::AccountAdded => "account-added",
+                IdlChangeKind::AccountRemoved => "account-removed",
+                IdlChangeKind::DiscriminatorChanged => "discriminator-changed",
+                IdlChangeKind::LayoutModeChanged => "layout-mode-changed",
+                IdlChangeKind::SizeChanged => "size-changed",
+                IdlChangeKind::FieldAdded => "field-added",
+                IdlChangeKind::FieldRemoved => "field-removed",
+                IdlChangeKind::FieldsReordered => "fields-reordered",
+                IdlChangeKind::TypeChanged => "type-changed",
+                IdlChangeKind::OffsetChanged => "offset-changed",
+                IdlChangeKind::BecameDynamic => "became-dynamic",
+            };
+            f.write_str(kind)
+        }
+    }
+
+    /// One difference between two IDL versions of an account.
+    #[derive(Debug, Clone, PartialEq, Serialize)]
+    pub struct IdlChange {
+        pub account: String,
+        /// Field path, or the parent path for reorders; absent for changes to the account as a whole
+        pub path: Option<String>,
+        pub kind: IdlChangeKind,
+        pub old: Option<String>,
+        pub new: Option<String>,
+        /// Whether existing account data or queries by field path stop decoding correctly
+        pub breaking: bool,
+    }
+
+    impl fmt::Display for IdlChange {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.account)?;
+            if let Some(path) = &self.path {
+                write!(f, ".{}", path)?;
+            }
+            write!(f, ": {}", self.kind)?;
+            match (&self.old, &self.new) {
+                (Some(old), Some(new)) => write!(f, " {} -> {}", old, new),
+                (Some(value), None) | (None, Some(value)) => write!(f, " {}", value),
+                (None, None) => Ok(()),
+            }
+        }
+    }
 
-    const DISCRIMINATOR_LEN: usize = 8;
+    /// Compares the accounts of two IDL versions, reporting every field path whose type, offset or size changed.
+    pub fn diff_idls(old_idl: &str, new_idl: &str) -> Result<Vec<IdlChange>, Box<dyn Error>> {
+        let old_json = parse_idl(old_idl)?;
+        let new_json = parse_idl(new_idl)?;
+        let old = (&*old_json, &build_types_map(&old_json)?);
+        let new = (&*new_json, &build_types_map(&new_json)?);
+        let old_accounts = idl_account_names(&old_json);
+        let new_accounts = idl_account_names(&new_json);
 
-    // Get program accounts by discriminator
-    pub fn get_program_accounts_with_discrim(
-        connection: &RpcClient,
-        program_address: &str,
-        discrim: &[u8],
-    ) -> Result<Vec<(Pubkey, Account)>, Box<dyn Error>> {
-        let discrim_base64 = BASE64_STANDARD.encode(discrim);
-        let memcmp = RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Base64(discrim_base64)));
-        
-        let config = create_program_account_config(vec![memcmp]);
-        
-        let accounts = connection
-            .get_program_accounts_with_config(&Pubkey::from_str(program_address)?, config)
-            .map_err(|e| format!("RPC error: {:?}", e))?;
-            
-        Ok(accounts)
+        let mut changes = Vec::new();
+        for account in &old_accounts {
+            if !new_accounts.contains(account) {
+                changes.push(account_change(account, IdlChangeKind::AccountRemoved, true));
+                continue;
+            }
+            diff_account(old, new, account, &mut changes)
+                .map_err(|e| format!("Failed to compare {}: {}", account, e))?;
+        }
+        for account in new_accounts.iter().filter(|account| !old_accounts.contains(account)) {
+            changes.push(account_change(account, IdlChangeKind::AccountAdded, false));
+        }
+        Ok(changes)
     }
 
-    // Helper function to create program account config with filters
-    fn create_program_account_config(filters: Vec<RpcFilterType>) -> RpcProgramAccountsConfig {
-        RpcProgramAccountsConfig {
-            filters: Some(filters),
-            account_config: RpcAccountInfoConfig {
-                encoding: Some(UiAccountEncoding::Base64),
-                ..Default::default()
-            },
-            ..Default::default()
+    // Helper to list account names in IDL order
+    fn idl_account_names(idl_json: &Value) -> Vec<String> {
+        let accounts = idl_json.get("accounts").and_then(|a| a.as_array());
+        accounts
+            .into_iter()
+            .flatten()
+            .filter_map(|account| account.get("name")?.as_str().map(str::to_string))
+            .collect()
+    }
+
+    // Helper to build a change to an account as a whole
+    fn account_change(account: &str, kind: IdlChangeKind, breaking: bool) -> IdlChange {
+        IdlChange { account: account.to_string(), path: None, kind, old: None, new: None, breaking }
+    }
+
+    // A parsed IDL along with its defined types
+    type ParsedIdl<'a> = (&'a Value, &'a HashMap<String, &'a Value>);
+
+    // Helper to compare one account present in both IDL versions
+    fn diff_account(old: ParsedIdl, new: ParsedIdl, account: &str, changes: &mut Vec<IdlChange>) -> Result<(), Box<dyn Error>> {
+        let change = |path: Option<&str>, kind, old: Option<String>, new: Option<String>, breaking| IdlChange {
+            account: account.to_string(),
+            path: path.map(str::to_string),
+            kind,
+            old,
+            new,
+            breaking,
+        };
+        let show = |value: Option<usize>| Some(value.map_or("dynamic".to_string(), |value| value.to_string()));
+
+        let old_discriminator = lookup_account_discriminator(old.0, account);
+        let new_discriminator = lookup_account_discriminator(new.0, account);
+        if old_discriminator != new_discriminator {
+            let hex = |discriminator: Option<Vec<u8>>| discriminator.map(|d| to_hex(&d));
+            changes.push(change(None, IdlChangeKind::DiscriminatorChanged, hex(old_discriminator), hex(new_discriminator), true));
+        }
+        let old_mode = account_layout_mode(old.0, old.1, account)?;
+        let new_mode = account_layout_mode(new.0, new.1, account)?;
+        if old_mode != new_mode {
+            changes.push(change(None, IdlChangeKind::LayoutModeChanged, Some(old_mode.to_string()), Some(new_mode.to_string()), true));
+        }
+
+        let old_layout = account_field_layouts(old.0, old.1, account, old_mode)?;
+        let new_layout = account_field_layouts(new.0, new.1, account, new_mode)?;
+        let old_size = account_layout_size(&old_layout);
+        let new_size = account_layout_size(&new_layout);
+        if let (Some(old), Some(new)) = (old_size, new_size) {
+            // Existing accounts are too short to hold a larger layout. A smaller one still decodes from the start of
+            // their data, and the fields it dropped are reported as breaking on their own
+            if old != new {
+                changes.push(change(None, IdlChangeKind::SizeChanged, show(old_size), show(new_size), new > old));
+            }
+        }
+
+        // Sibling order under each parent, compared over the fields both versions share
+        let siblings = |layout: &[FieldLayout], parent: &str| -> Vec<String> {
+            layout
+                .iter()
+                .filter(|field| field.path.rsplit_once('.').map_or("", |(p, _)| p) == parent)
+                .map(|field| field.path.clone())
+                .collect()
+        };
+        let mut parents: Vec<&str> = vec![""];
+        parents.extend(old_layout.iter().map(|field| field.path.as_str()));
+        for parent in parents {
+            let old_order = siblings(&old_layout, parent);
+            let new_order = siblings(&new_layout, parent);
+            let shared_old: Vec<&String> = old_order.iter().filter(|path| new_order.contains(path)).collect();
+            let shared_new: Vec<&String> = new_order.iter().filter(|path| old_order.contains(path)).collect();
+            if shared_old != shared_new {
+                let names = |order: &[&String]| {
+                    let names: Vec<&str> = order.iter().map(|path| path.rsplit('.').next().unwrap_or(path)).collect();
+                    Some(names.join(", "))
+                };
+                let path = Some(parent).filter(|parent| !parent.is_empty());
+                changes.push(change(path, IdlChangeKind::FieldsReordered, names(&shared_old), names(&shared_new), true));
+            }
         }
+
+        for old_field in &old_layout {
+            let path = Some(old_field.path.as_str());
+            let Some(new_field) = new_layout.iter().find(|field| field.path == old_field.path) else {
+                changes.push(change(path, IdlChangeKind::FieldRemoved, Some(old_field.field_type.clone()), None, true));
+                continue;
+            };
+            if old_field.field_type != new_field.field_type {
+                let (old_type, new_type) = (old_field.field_type.clone(), new_field.field_type.clone());
+                changes.push(change(path, IdlChangeKind::TypeChanged, Some(old_type), Some(new_type), true));
+            } else if old_field.size != new_field.size {
+                changes.push(change(path, IdlChangeKind::SizeChanged, show(old_field.size), show(new_field.size), true));
+            }
+            match (old_field.offset, new_field.offset) {
+                (Some(old), Some(new)) if old != new => {
+                    changes.push(change(path, IdlChangeKind::OffsetChanged, show(Some(old)), show(Some(new)), true));
+                }
+                (Some(old), None) => {
+                    changes.push(change(path, IdlChangeKind::BecameDynamic, show(Some(old)), show(None), true));
+                }
+                _ => {}
+            }
+        }
+        for new_field in new_layout.iter().filter(|field| !old_layout.iter().any(|old| old.path == field.path)) {
+            let path = Some(new_field.path.as_str());
+            changes.push(change(path, IdlChangeKind::FieldAdded, None, Some(new_field.field_type.clone()), false));
+        }
+        Ok(())
     }
 
-    // Decode message data
-    pub fn decode_message(message_data: &str) {
-        let decoded_data = BASE64_STANDARD
-            .decode(message_data)
-            .expect("Failed to decode message");
-        let message: Message = bincode::deserialize(&decoded_data).unwrap();
-        let tx = Transaction::new_unsigned(message);
-        println_transaction(&VersionedTransaction::from(tx), None, " ", None, None);
+    /// An IDL account type matched by discriminator, with how the data length compares to its layout.
+    #[derive(Debug, Clone, Serialize)]
+    pub struct AccountTypeMatch {
+        pub program_name: Option<String>,
+        pub program: Option<String>,
+        pub account: String,
+        pub data_len: usize,
+        /// Size of the layout including the discriminator, when every field has a fixed size
+        pub layout_size: Option<usize>,
+        /// Bytes used when decoding the data with the layout, if it decodes
+        pub decoded_len: Option<usize>,
     }
 
-    // Find accounts by variable value
-    pub fn find_accounts_by_variable(
-        connection: &RpcClient,
-        program_address: &str,
-        discrim: &[u8],
-        variable_offset: usize,
-        variable_value: &[u8],
-    ) -> Result<Vec<(Pubkey, Account)>, Box<dyn Error>> {
-        let program_pubkey = Pubkey::from_str(program_address)?;
+    impl AccountTypeMatch {
+        /// Tells whether the data length fits the layout: fixed layouts must match it exactly, dynamic ones only need to decode.
+        pub fn is_size_consistent(&self) -> bool {
+            match self.layout_size {
+                Some(size) => size == self.data_len,
+                None => self.decoded_len.is_some(),
+            }
+        }
+    }
 
-        let discrim_filter = RpcFilterType::Memcmp(Memcmp::new(0, MemcmpEncodedBytes::Bytes(discrim.into())));
-        let variable_filter = RpcFilterType::Memcmp(Memcmp::new(
-            variable_offset,
-            MemcmpEncodedBytes::Bytes(variable_value.into()),
-        ));
+    impl fmt::Display for AccountTypeMatch {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}", self.account)?;
+            match (&self.program_name, &self.program) {
+                (Some(name), Some(program)) => write!(f, " ({} {})", name, program)?,
+                (Some(name), None) => write!(f, " ({})", name)?,
+                (None, Some(program)) => write!(f, " ({})", program)?,
+                (None, None) => {}
+            }
+            write!(f, ": {} bytes, ", self.data_len)?;
+            match (self.layout_size, self.decoded_len) {
+                (Some(size), _) if size == self.data_len => write!(f, "matches the layout size"),
+                (Some(size), _) => write!(f, "layout size is {} bytes", size),
+                (None, Some(decoded)) => write!(f, "dynamic layout decodes {} bytes", decoded),
+                (None, None) => write!(f, "data does not decode with the layout"),
+            }
+        }
+    }
 
-        let config = create_program_account_config(vec![discrim_filter, variable_filter]);
+    /// Identifies which account type in the IDL the data belongs to and checks its length against the layout.
+    pub fn identify_account_data(idl: &str, data: &[u8]) -> Result<Option<AccountTypeMatch>, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let Some(account_name) = detect_account_name(&idl_json, data)? else {
+            return Ok(None);
+        };
+        let types_map = build_types_map(&idl_json)?;
+        let fields = extract_account_fields(find_account_definition(&idl_json, &account_name)?)?;
 
-        let accounts = connection.get_program_accounts_with_config(&program_pubkey, config)?;
-        Ok(accounts)
+        let mode = account_layout_mode(&idl_json, &types_map, &account_name)?;
+        let layout_size = account_layout_size(&account_field_layouts(&idl_json, &types_map, &account_name, mode)?);
+        let decoded_len = decode_struct_fields(data, DISCRIMINATOR_LEN, fields, &mut TypeResolver::new(&types_map))
+            .ok()
+            .map(|(_, size)| DISCRIMINATOR_LEN + size);
+
+        Ok(Some(AccountTypeMatch {
+            program_name: idl_json
+                .get("name")
+                .or_else(|| idl_json.get("metadata").and_then(|m| m.get("name")))
+                .and_then(|n| n.as_str())
+                .map(String::from),
+            program: idl_program_address(&idl_json),
+            account: account_name,
+            data_len: data.len(),
+            layout_size,
+            decoded_len,
+        }))
     }
 
-    // Calculate discriminator for an account
-    pub fn calculate_discriminator(account_name: &str) -> [u8; DISCRIMINATOR_LEN] {
-        let mut hasher = Sha256::new();
-        hasher.update(format!("account:{}", account_name).as_bytes());
-        let hash = hasher.finalize();
-        let mut discriminator = [0u8; DISCRIMINATOR_LEN];
-        discriminator.copy_from_slice(&hash[..DISCRIMINATOR_LEN]);
-        discriminator
+    /// Looks up a named account's discriminator in the IDL, preferring an explicit one over the hash of its name.
+    pub fn find_account_discriminator(idl: &str, account_name: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        lookup_account_discriminator(&idl_json, account_name)
+            .ok_or_else(|| format!("Account '{}' not found in IDL or has an invalid discriminator", account_name).into())
     }
 
-    // Find accounts by criteria (comprehensive search)
-    pub fn find_accounts_by_criteria(
-        connection: &RpcClient,
-        idl: &str,
-        program_address: &str,
-        account_name: &str,
-        variable_path: &str,
-        variable_value: &[u8],
-    ) -> Result<Vec<(Pubkey, Account)>, Box<dyn Error>> {
-        // Calculate the discriminator for the account name
-        let discriminator = calculate_discriminator(account_name);
+    // Helper to get a named account's discriminator from the IDL's 'accounts' list
+    fn lookup_account_discriminator(idl_json: &Value, account_name: &str) -> Option<Vec<u8>> {
+        idl_json
+            .get("accounts")?
+            .as_array()?
+            .iter()
+            .find(|acc| acc.get("name").is_some_and(|name| name == account_name))
+            .and_then(|account| idl_discriminator(account, calculate_discriminator))
+    }
 
-        // Get the offset of the variable
-        let variable_offset = get_variable_offset_from_idl(idl, account_name, variable_path)?;
+    /// Reads the program address embedded in the IDL (`address` or `metadata.address`).
+    pub fn get_program_address_from_idl(idl: &str) -> Result<String, Box<dyn Error>> {
+        idl_program_address(&parse_idl(idl)?).ok_or_else(|| "IDL does not contain a program address".into())
+    }
 
-        // Encode the discriminator and variable value in Base64
-        let discriminator_base64 = BASE64_STANDARD.encode(discriminator);
-        let variable_value_base64 = BASE64_STANDARD.encode(variable_value);
+    // Helper to read the program address from an already parsed IDL
+    fn idl_program_address(idl_json: &Value) -> Option<String> {
+        idl_json
+            .get("address")
+            .or_else(|| idl_json.get("metadata").and_then(|m| m.get("address")))
+            .and_then(|a| a.as_str())
+            .map(String::from)
+    }
 
-        // Fetch accounts matching the discriminator and variable value
-        let program_pubkey = Pubkey::from_str(&program_address)?;
+    // Seed Anchor uses to derive a program's IDL account from its signer PDA
+    const IDL_ACCOUNT_SEED: &str = "anchor:idl";
 
-        let discrim_filter = RpcFilterType::Memcmp(
-            Memcmp::new(0, MemcmpEncodedBytes::Base64(discriminator_base64))
-        );
-        let variable_filter = RpcFilterType::Memcmp(
-            Memcmp::new(variable_offset, MemcmpEncodedBytes::Base64(variable_value_base64))
-        );
+    /// Derives the address of the account where Anchor stores a program's IDL.
+    pub fn idl_account_address(program_id: &Pubkey) -> Result<Pubkey, Box<dyn Error>> {
+        let (base, _) = Pubkey::find_program_address(&[], program_id);
+        Ok(Pubkey::create_with_seed(&base, IDL_ACCOUNT_SEED, program_id)?)
+    }
 
-        let config = create_program_account_config(vec![discrim_filter, variable_filter]);
-        let accounts = connection.get_program_accounts_with_config(&program_pubkey, config)?;
-        
-        Ok(accounts)
+    /// Decompresses the IDL JSON stored in an Anchor IDL account.
+    pub fn decode_idl_account(data: &[u8]) -> Result<String, Box<dyn Error>> {
+        if data.get(..DISCRIMINATOR_LEN) != Some(&calculate_discriminator("IdlAccount")[..]) {
+            return Err("Account is not an Anchor IDL account".into());
+        }
+        // The discriminator is followed by the upgrade authority and the length of the compressed IDL
+        let len_offset = DISCRIMINATOR_LEN + 32;
+        let compressed_len = read_len(data, len_offset)?;
+        let compressed = read_bytes(data, len_offset + 4, compressed_len, "compressed IDL")?;
+
+        let mut idl = String::new();
+        flate2::read::ZlibDecoder::new(compressed)
+            .read_to_string(&mut idl)
+            .map_err(|e| format!("Failed to decompress IDL: {}", e))?;
+        parse_idl(&idl).map_err(|e| format!("IDL account does not hold valid IDL JSON: {}", e))?;
+        Ok(idl)
     }
 
-    // IDL parsing and variable resolving functions
-    pub fn get_variable_offset_from_idl(
-        idl: &str,
-        account_name: &str,
-        variable_path: &str,
-    ) -> Result<usize, Box<dyn Error>> {
-        // Parse the IDL JSON
-        let idl_json: Value = serde_json::from_str(idl)?;
-        let types_map = build_types_map(&idl_json)?;
-        
-        // Find the account definition in the IDL
-        let account = find_account_in_idl(&idl_json, account_name)?;
+    /// Fetches and decompresses the IDL a program published on chain.
+    pub fn fetch_onchain_idl(connection: &RpcClient, program_id: &Pubkey) -> Result<String, Box<dyn Error>> {
+        let address = idl_account_address(program_id)?;
+        let account = connection
+            .get_account_with_commitment(&address, connection.commitment())?
+            .value
+            .ok_or_else(|| format!("Program {} has no IDL account at {}", program_id, address))?;
+        decode_idl_account(&account.data)
+    }
 
-        // Split the variable path into components (e.g., "pricing.tradeImpactFeeScalar")
-        let path_parts: Vec<&str> = variable_path.split('.').collect();
+    /// Loads a program's on-chain IDL, reusing the copy saved as `<program>.json` in the cache directory.
+    pub fn load_program_idl(
+        connection: &RpcClient,
+        program_id: &Pubkey,
+        cache_dir: &Path,
+        refresh: bool,
+    ) -> Result<String, Box<dyn Error>> {
+        let cache_path = cache_dir.join(format!("{}.json", program_id));
+        if !refresh {
+            // A truncated or corrupt cache entry is fetched again rather than used
+            if let Ok(idl) = std::fs::read_to_string(&cache_path) {
+                if serde_json::from_str::<Value>(&idl).is_ok() {
+                    return Ok(idl);
+                }
+            }
+        }
+        let idl = fetch_onchain_idl(connection, program_id)?;
+        std::fs::create_dir_all(cache_dir)?;
+        std::fs::write(&cache_path, &idl)?;
+        Ok(idl)
+    }
 
-        // Calculate the offset of the variable
-        let mut offset = 0;
-        let mut current_fields = extract_account_fields(account)?;
+    /// File in a registry directory that maps program ids to IDL files.
+    pub const IDL_REGISTRY_FILE: &str = "registry.toml";
 
-        for part in path_parts.clone() {
-            let mut field_found = false;
+    /// IDL files for the programs known locally, keyed by program id.
+    #[derive(Debug, Clone, Default)]
+    pub struct IdlRegistry {
+        pub dir: PathBuf,
+        programs: BTreeMap<String, PathBuf>,
+    }
 
-            for field in current_fields {
-                let field_name = field
-                    .get("name")
-                    .and_then(|n| n.as_str())
-                    .ok_or("Field has no name")?;
+    impl IdlRegistry {
+        /// Loads a registry directory: every IDL JSON file that names its program address, overridden by the
+        /// `[programs]` table of registry.toml, whose paths are relative to the directory.
+        pub fn load(dir: &Path) -> Result<Self, Box<dyn Error>> {
+            let mut programs = BTreeMap::new();
+            if let Ok(entries) = std::fs::read_dir(dir) {
+                let mut files: Vec<PathBuf> = entries
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+                    .collect();
+                files.sort();
+                for path in files {
+                    // Files without an address can still be registered through the mapping
+                    let Ok(idl) = std::fs::read_to_string(&path) else {
+                        continue;
+                    };
+                    if let Ok(program) = get_program_address_from_idl(&idl) {
+                        programs.entry(program).or_insert(path);
+                    }
+                }
+            }
 
-                let field_type = field.get("type").ok_or("Field has no type")?;
+            let mapping_path = dir.join(IDL_REGISTRY_FILE);
+            if mapping_path.is_file() {
+                let mapping: toml::Value = toml::from_str(&std::fs::read_to_string(&mapping_path)?)
+                    .map_err(|e| format!("Invalid {}: {}", mapping_path.display(), e))?;
+                let table = mapping
+                    .get("programs")
+                    .and_then(|p| p.as_table())
+                    .ok_or_else(|| format!("{} has no [programs] table", mapping_path.display()))?;
+                for (program, path) in table {
+                    Pubkey::from_str(program)
+                        .map_err(|_| format!("Invalid program id {} in {}", program, mapping_path.display()))?;
+                    let path = path
+                        .as_str()
+                        .ok_or_else(|| format!("IDL path for {} in {} is not a string", program, mapping_path.display()))?;
+                    programs.insert(program.clone(), dir.join(path));
+                }
+            }
+            Ok(IdlRegistry { dir: dir.to_path_buf(), programs })
+        }
+
+        /// Returns the path of the IDL file registered for a program, if there is one.
+        pub fn idl_path(&self, program_id: &str) -> Option<&Path> {
+            self.programs.get(program_id).map(PathBuf::as_path)
+        }
+
+        /// Reads the IDL registered for a program, if there is one.
+        pub fn load_idl(&self, program_id: &str) -> Result<Option<String>, Box<dyn Error>> {
+            let Some(path) = self.idl_path(program_id) else {
+                return Ok(None);
+            };
+            let idl = std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            Ok(Some(idl))
+        }
+
+        /// Lists the registered program ids and their IDL paths, in program id order.
+        pub fn programs(&self) -> impl Iterator<Item = (&str, &Path)> {
+            self.programs.iter().map(|(program, path)| (program.as_str(), path.as_path()))
+        }
+    }
+
+    // IDLs parsed so far, keyed by their text, so a program's IDL is parsed and instantiated once per process
+    static PARSED_IDLS: Mutex<BTreeMap<String, Arc<Value>>> = Mutex::new(BTreeMap::new());
+
+    // Parse an IDL, instantiating generic types so everything else only sees concrete ones
+    fn parse_idl(idl: &str) -> Result<Arc<Value>, Box<dyn Error>> {
+        if let Some(parsed) = PARSED_IDLS.lock().unwrap_or_else(PoisonError::into_inner).get(idl) {
+            return Ok(parsed.clone());
+        }
+        let parsed = Arc::new(instantiate_idl_generics(serde_json::from_str(idl)?)?);
+        PARSED_IDLS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(idl.to_string(), parsed.clone());
+        Ok(parsed)
+    }
 
-                if field_name == part {
-                    field_found = true;
+    // Helper to add an instantiation of every generic type the IDL uses and point its uses at them
+    fn instantiate_idl_generics(mut idl_json: Value) -> Result<Value, Box<dyn Error>> {
+        let generic_types: HashMap<String, Value> = idl_json
+            .get("types")
+            .and_then(|t| t.as_array())
+            .into_iter()
+            .flatten()
+            .filter(|definition| definition.get("generics").and_then(|g| g.as_array()).is_some_and(|g| !g.is_empty()))
+            .filter_map(|definition| Some((definition.get("name")?.as_str()?.to_string(), definition.clone())))
+            .collect();
+        if generic_types.is_empty() {
+            return Ok(idl_json);
+        }
 
-                    if part == *path_parts.last().unwrap() {
-                        // If this is the last part of the path, return the offset
-                        return Ok(offset + DISCRIMINATOR_LEN);
+        // Generic definitions themselves are left alone, only concrete uses of them are instantiated
+        let mut instances = BTreeMap::new();
+        if let Some(sections) = idl_json.as_object_mut() {
+            for (section, value) in sections.iter_mut() {
+                match (section.as_str(), value) {
+                    ("types", Value::Array(types)) => {
+                        for definition in types.iter_mut().filter(|d| !generic_types.contains_key(d["name"].as_str().unwrap_or_default())) {
+                            instantiate_generics(definition, &generic_types, &mut instances)?;
+                        }
                     }
+                    (_, value) => instantiate_generics(value, &generic_types, &mut instances)?,
+                }
+            }
+        }
+        if let Some(types) = idl_json.get_mut("types").and_then(|t| t.as_array_mut()) {
+            types.extend(instances.into_values());
+        }
+        Ok(idl_json)
+    }
 
-                    // Resolve the type and continue traversing
-                    current_fields = resolve_nested_fields(field_type, &types_map)?;
-                    break;
+    // Helper to replace references to generic types with references to their instantiations
+    fn instantiate_generics(
+        value: &mut Value,
+        generic_types: &HashMap<String, Value>,
+        instances: &mut BTreeMap<String, Value>,
+    ) -> Result<(), Box<dyn Error>> {
+        let generic_args = value
+            .get("defined")
+            .and_then(|defined| defined.get("generics"))
+            .and_then(|g| g.as_array())
+            .filter(|args| !args.is_empty())
+            .cloned();
+        if let Some(args) = generic_args {
+            let name = defined_type_name(value).ok_or("Invalid 'defined' type")?.to_string();
+            let definition = generic_types
+                .get(&name)
+                .ok_or_else(|| format!("Generic arguments given for non-generic type: {}", name))?;
+            let instance_name = format!("{}<{}>", name, args.iter().map(format_generic_arg).collect::<Vec<_>>().join(", "));
+
+            if !instances.contains_key(&instance_name) {
+                let params = definition["generics"].as_array().ok_or("Generics must be an array")?;
+                if params.len() != args.len() {
+                    return Err(format!("{} expects {} generic arguments, got {}", name, params.len(), args.len()).into());
+                }
+                let mut bindings = HashMap::new();
+                for (param, arg) in params.iter().zip(&args) {
+                    let param_name = param.get("name").and_then(|n| n.as_str()).ok_or("Generic parameter has no name")?;
+                    bindings.insert(param_name.to_string(), generic_arg_value(arg)?);
                 }
 
-                // Accumulate the size of the current field
-                offset += calculate_field_size(field_type, &types_map)?;
+                let mut instance = substitute_generics(definition, &bindings);
+                instance["name"] = Value::String(instance_name.clone());
+                if let Some(obj) = instance.as_object_mut() {
+                    obj.remove("generics");
+                }
+                // Reserve the name first so a type that refers to itself does not recurse forever
+                instances.insert(instance_name.clone(), Value::Null);
+                instantiate_generics(&mut instance, generic_types, instances)?;
+                instances.insert(instance_name.clone(), instance);
+            }
+            *value = serde_json::json!({ "defined": instance_name });
+            return Ok(());
+        }
+
+        match value {
+            Value::Object(obj) => {
+                for nested in obj.values_mut() {
+                    instantiate_generics(nested, generic_types, instances)?;
+                }
+            }
+            Value::Array(items) => {
+                for nested in items {
+                    instantiate_generics(nested, generic_types, instances)?;
+                }
             }
+            _ => {}
+        }
+        Ok(())
+    }
 
-            if !field_found {
-                return Err(format!("Field '{}' not found", part).into());
+    // Helper to get the type or constant a generic argument binds
+    fn generic_arg_value(arg: &Value) -> Result<Value, Box<dyn Error>> {
+        match arg.get("kind").and_then(|k| k.as_str()) {
+            Some("type") => arg.get("type").cloned().ok_or_else(|| "Generic type argument has no type".into()),
+            // Const arguments are given as strings, but array lengths must be numbers
+            Some("const") => {
+                let value = arg.get("value").ok_or("Generic const argument has no value")?;
+                match value.as_str() {
+                    Some(number) => Ok(Value::from(number.parse::<u64>()?)),
+                    None => Ok(value.clone()),
+                }
             }
+            _ => Err(format!("Unsupported generic argument: {}", arg).into()),
+        }
+    }
+
+    // Helper to format a generic argument for an instantiated type name
+    fn format_generic_arg(arg: &Value) -> String {
+        match arg.get("type") {
+            Some(arg_type) => format_idl_type(arg_type),
+            None => arg.get("value").map(display_value).unwrap_or_default(),
         }
+    }
 
-        Err("Variable not found in account fields".into())
+    // Helper to replace every `{"generic": name}` in a definition with the value bound to it
+    fn substitute_generics(value: &Value, bindings: &HashMap<String, Value>) -> Value {
+        if let Some(bound) = value.get("generic").and_then(|g| g.as_str()).and_then(|name| bindings.get(name)) {
+            return bound.clone();
+        }
+        match value {
+            Value::Object(obj) => Value::Object(
+                obj.iter()
+                    .map(|(key, nested)| (key.clone(), substitute_generics(nested, bindings)))
+                    .collect(),
+            ),
+            Value::Array(items) => Value::Array(items.iter().map(|nested| substitute_generics(nested, bindings)).collect()),
+            other => other.clone(),
+        }
     }
 
     // Helper function to build types map from IDL
@@ -215,6 +2980,17 @@ pub mod tools {
             .ok_or_else(|| "Account not found in IDL".into())
     }
 
+    // Helper to find the definition holding an account's layout. Newer IDLs only list the name and discriminator
+    // under 'accounts' and define the layout in the type of the same name
+    fn find_account_definition<'a>(idl_json: &'a Value, account_name: &str) -> Result<&'a Value, Box<dyn Error>> {
+        let account = find_account_in_idl(idl_json, account_name)?;
+        if account.get("type").is_some() {
+            return Ok(account);
+        }
+        let types = idl_json.get("types").and_then(|t| t.as_array());
+        Ok(types.into_iter().flatten().find(|t| t.get("name") == account.get("name")).unwrap_or(account))
+    }
+
     // Helper function to extract account fields
     fn extract_account_fields(account: &Value) -> Result<&Vec<Value>, Box<dyn Error>> {
         account
@@ -230,7 +3006,7 @@ pub mod tools {
         field_type: &Value,
         types_map: &'a HashMap<String, &Value>,
     ) -> Result<&'a Vec<Value>, Box<dyn Error>> {
-        if let Some(defined_type) = field_type.get("defined").and_then(|t| t.as_str()) {
+        if let Some(defined_type) = defined_type_name(field_type) {
             let custom_type_def = types_map
                 .get(defined_type)
                 .ok_or_else(|| format!("Unknown defined type: {}", defined_type))?;
@@ -246,118 +3022,366 @@ pub mod tools {
         }
     }
 
-    // Calculate field sizes for offset determination
-    fn calculate_field_size(
-        field_type: &Value,
-        types_map: &HashMap<String, &Value>,
-    ) -> Result<usize, Box<dyn Error>> {
-        match field_type {
-            Value::String(type_str) => match type_str.as_str() {
-                "u8" | "i8" => Ok(1),
-                "u16" | "i16" => Ok(2),
-                "u32" | "i32" | "f32" => Ok(4),
-                "u64" | "i64" | "f64" => Ok(8),
-                "u128" | "i128" => Ok(16),
-                "bool" => Ok(1),
-                "publicKey" => Ok(32),
-                "string" => Err("Dynamic size types like 'string' are not supported".into()),
-                custom_type => {
-                    let custom_type_def = types_map
-                        .get(custom_type)
-                        .ok_or_else(|| format!("Unknown custom type: {}", custom_type))?;
-                    calculate_custom_type_size(custom_type_def, types_map)
+    // Deepest chain of nested defined types the resolver will follow
+    const MAX_TYPE_DEPTH: usize = 64;
+
+    // Error for types whose size depends on the data, as opposed to types the IDL does not describe
+    #[derive(Debug)]
+    struct DynamicSizeError(String);
+
+    impl fmt::Display for DynamicSizeError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Dynamic size types like '{}' are not supported", self.0)
+        }
+    }
+
+    impl Error for DynamicSizeError {}
+
+    // Resolves defined types by name, caching computed sizes and rejecting recursive or overly deep definitions
+    struct TypeResolver<'a> {
+        types_map: &'a HashMap<String, &'a Value>,
+        sizes: HashMap<String, usize>,
+        min_sizes: HashMap<String, usize>,
+        zero_copy_sizes: HashMap<(String, bool), (usize, usize)>,
+        /// Types being resolved, with the data offset they were entered at when walking a value
+        chain: Vec<(String, Option<usize>)>,
+        /// Whether options are sized by the data rather than as their inner type
+        dynamic_options: bool,
+    }
+
+    impl<'a> TypeResolver<'a> {
+        fn new(types_map: &'a HashMap<String, &'a Value>) -> Self {
+            TypeResolver {
+                types_map,
+                sizes: HashMap::new(),
+                min_sizes: HashMap::new(),
+                zero_copy_sizes: HashMap::new(),
+                chain: Vec::new(),
+                dynamic_options: false,
+            }
+        }
+
+        // Resolver for account layouts, where an option's size depends on whether it holds a value
+        fn for_layout(types_map: &'a HashMap<String, &'a Value>) -> Self {
+            TypeResolver { dynamic_options: true, ..TypeResolver::new(types_map) }
+        }
+
+        // Look up a defined type and push it onto the chain of types being resolved
+        fn enter(&mut self, name: &str) -> Result<&'a Value, Box<dyn Error>> {
+            self.enter_at(name, None)
+        }
+
+        // Enter a defined type while walking a value at a data offset. A type may come back once bytes have been
+        // consumed since it was entered, as in a list behind an option, but not at the same offset
+        fn enter_at(&mut self, name: &str, offset: Option<usize>) -> Result<&'a Value, Box<dyn Error>> {
+            let definition = *self
+                .types_map
+                .get(name)
+                .ok_or_else(|| format!("Unknown defined type: {}", name))?;
+            let names = |chain: &[(String, Option<usize>)]| {
+                chain.iter().map(|(t, _)| t.as_str()).collect::<Vec<_>>().join(" -> ")
+            };
+            let repeat = self.chain.iter().position(|(t, entered)| t == name && (offset.is_none() || *entered == offset));
+            if let Some(start) = repeat {
+                return Err(format!("Recursive type: {} -> {}", names(&self.chain[start..]), name).into());
+            }
+            if self.chain.len() >= MAX_TYPE_DEPTH {
+                return Err(format!("Type nesting exceeds {} levels: {} -> {}", MAX_TYPE_DEPTH, names(&self.chain), name).into());
+            }
+            self.chain.push((name.to_string(), offset));
+            Ok(definition)
+        }
+
+        fn leave(&mut self) {
+            self.chain.pop();
+        }
+
+        // Calculate field sizes for offset determination
+        fn field_size(&mut self, field_type: &Value) -> Result<usize, Box<dyn Error>> {
+            match field_type {
+                Value::String(type_str) => match type_str.as_str() {
+                    "u8" | "i8" => Ok(1),
+                    "u16" | "i16" => Ok(2),
+                    "u32" | "i32" | "f32" => Ok(4),
+                    "u64" | "i64" | "f64" => Ok(8),
+                    "u128" | "i128" => Ok(16),
+                    "bool" => Ok(1),
+                    "publicKey" | "pubkey" => Ok(32),
+                    "string" | "bytes" => Err(DynamicSizeError(type_str.clone()).into()),
+                    custom_type => self.defined_size(custom_type),
+                },
+                Value::Object(obj) if obj.get("array").is_some() => {
+                    let array = obj.get("array").ok_or("Array type is invalid")?;
+                    let array_type = array.get(0).ok_or("Array type is missing")?;
+                    let array_length = array
+                        .get(1)
+                        .and_then(|len| len.as_u64())
+                        .ok_or("Array length is invalid")?;
+                    Ok(self.field_size(array_type)? * array_length as usize)
                 }
-            },
-            Value::Object(obj) if obj.get("array").is_some() => {
-                let array = obj.get("array").ok_or("Array type is invalid")?;
-                let array_type = array.get(0).ok_or("Array type is missing")?;
-                let array_length = array
-                    .get(1)
-                    .and_then(|len| len.as_u64())
-                    .ok_or("Array length is invalid")?;
-                Ok(calculate_field_size(array_type, types_map)? * array_length as usize)
+                Value::Object(obj) if obj.get("defined").is_some() => {
+                    let defined_type = defined_type_name(field_type).ok_or("Invalid 'defined' type")?;
+                    self.defined_size(defined_type)
+                }
+                Value::Object(obj) if obj.get("option").is_some() => {
+                    if self.dynamic_options {
+                        return Err(DynamicSizeError(format_idl_type(field_type)).into());
+                    }
+                    let option_type = obj.get("option").ok_or("Option type is invalid")?;
+                    self.field_size(option_type)
+                }
+                Value::Object(obj) if obj.get("coption").is_some() => {
+                    let coption_type = obj.get("coption").ok_or("COption type is invalid")?;
+                    Ok(4 + self.field_size(coption_type)?)
+                }
+                Value::Object(obj) if obj.get("vec").is_some() => Err(DynamicSizeError(format_idl_type(field_type)).into()),
+                Value::Object(obj) if obj.get("tuple").is_some() => {
+                    let tuple = obj.get("tuple").ok_or("Tuple type is invalid")?;
+                    let tuple_elements = tuple
+                        .as_array()
+                        .ok_or("Tuple elements must be an array")?;
+                    let mut size = 0;
+                    for element in tuple_elements {
+                        size += self.field_size(element)?;
+                    }
+                    Ok(size)
+                }
+                _ => Err(format!("Unsupported field type: {:?}", field_type).into()),
             }
-            Value::Object(obj) if obj.get("defined").is_some() => {
-                let defined_type = obj
-                    .get("defined")
-                    .and_then(|t| t.as_str())
-                    .ok_or("Invalid 'defined' type")?;
-                let custom_type_def = types_map
-                    .get(defined_type)
-                    .ok_or_else(|| format!("Unknown defined type: {}", defined_type))?;
-                calculate_custom_type_size(custom_type_def, types_map)
+        }
+
+        // Size a defined type once, reusing the result for every later field of that type
+        fn defined_size(&mut self, name: &str) -> Result<usize, Box<dyn Error>> {
+            if let Some(size) = self.sizes.get(name) {
+                return Ok(*size);
             }
-            Value::Object(obj) if obj.get("option").is_some() => {
-                let option_type = obj.get("option").ok_or("Option type is invalid")?;
-                calculate_field_size(option_type, types_map)
+            let definition = self.enter(name)?;
+            let size = self.custom_type_size(definition);
+            self.leave();
+            let size = size?;
+            self.sizes.insert(name.to_string(), size);
+            Ok(size)
+        }
+
+        fn custom_type_size(&mut self, custom_type_def: &Value) -> Result<usize, Box<dyn Error>> {
+            let type_kind = custom_type_def
+                .get("type")
+                .ok_or("Custom type does not contain 'type'")?;
+
+            match type_kind.get("kind").and_then(|k| k.as_str()) {
+                Some("struct") => {
+                    let fields = type_kind
+                        .get("fields")
+                        .ok_or("Struct type does not contain 'fields'")?
+                        .as_array()
+                        .ok_or("'fields' is not an array")?;
+
+                    let mut size = 0;
+                    for field in fields {
+                        let field_type = field.get("type").ok_or("Field has no type")?;
+                        size += self.field_size(field_type)?;
+                    }
+                    Ok(size)
+                }
+                Some("enum") => {
+                    let variants = type_kind
+                        .get("variants")
+                        .ok_or("Enum type does not contain 'variants'")?
+                        .as_array()
+                        .ok_or("'variants' is not an array")?;
+
+                    // Borsh writes only the chosen variant, so the size is fixed only when every variant has the same size
+                    let mut variant_sizes = Vec::with_capacity(variants.len());
+                    for variant in variants {
+                        let empty_vec = Vec::new();
+                        let variant_fields = variant
+                                .get("fields")
+                                .and_then(|f| f.as_array())
+                                .unwrap_or(&empty_vec);
+
+                        let mut variant_size = 0;
+                        for field in variant_fields {
+                            // Named variant fields wrap their type, tuple variant fields are the type itself
+                            variant_size += self.field_size(field.get("type").unwrap_or(field))?;
+                        }
+                        variant_sizes.push(variant_size);
+                    }
+                    match variant_sizes.first() {
+                        Some(size) if variant_sizes.iter().any(|other| other != size) => {
+                            let name = custom_type_def.get("name").and_then(|n| n.as_str()).unwrap_or("enum");
+                            Err(DynamicSizeError(name.to_string()).into())
+                        }
+                        size => Ok(1 + size.copied().unwrap_or(0)), // 1 byte for the discriminant
+                    }
+                }
+                _ => Err("Unsupported custom type kind".into()),
+            }
+        }
+
+        // Get the fewest bytes a Borsh value of a type can take, counting the smallest choice for options and enums
+        fn min_size(&mut self, field_type: &Value) -> Result<usize, Box<dyn Error>> {
+            match field_type {
+                Value::String(type_str) => match type_str.as_str() {
+                    "string" | "bytes" => Ok(4),
+                    primitive if !self.types_map.contains_key(primitive) => self.field_size(field_type),
+                    custom_type => self.defined_min_size(custom_type),
+                },
+                Value::Object(obj) if obj.get("array").is_some() => {
+                    let item_type = obj["array"].get(0).ok_or("Array type is missing")?;
+                    let count = obj["array"].get(1).and_then(|len| len.as_u64()).ok_or("Array length is invalid")?;
+                    Ok(self.min_size(item_type)? * count as usize)
+                }
+                Value::Object(obj) if obj.get("vec").is_some() => Ok(4),
+                Value::Object(obj) if obj.get("option").is_some() => Ok(1),
+                // An empty COption still holds the bytes of its inner type
+                Value::Object(obj) if obj.get("coption").is_some() => Ok(4 + self.min_size(&obj["coption"])?),
+                Value::Object(obj) if obj.get("tuple").is_some() => {
+                    let elements = obj["tuple"].as_array().ok_or("Tuple elements must be an array")?;
+                    let mut size = 0;
+                    for element in elements {
+                        size += self.min_size(element)?;
+                    }
+                    Ok(size)
+                }
+                _ => {
+                    let name = defined_type_name(field_type).ok_or_else(|| format!("Unsupported field type: {:?}", field_type))?;
+                    self.defined_min_size(name)
+                }
             }
-            Value::Object(obj) if obj.get("coption").is_some() => {
-                let coption_type = obj.get("coption").ok_or("COption type is invalid")?;
-                calculate_field_size(coption_type, types_map)
+        }
+
+        fn defined_min_size(&mut self, name: &str) -> Result<usize, Box<dyn Error>> {
+            if let Some(size) = self.min_sizes.get(name) {
+                return Ok(*size);
             }
-            Value::Object(obj) if obj.get("tuple").is_some() => {
-                let tuple = obj.get("tuple").ok_or("Tuple type is invalid")?;
-                let tuple_elements = tuple
-                    .as_array()
-                    .ok_or("Tuple elements must be an array")?;
-                let mut size = 0;
-                for element in tuple_elements {
-                    size += calculate_field_size(element, types_map)?;
+            let definition = self.enter(name)?;
+            let size = self.custom_type_min_size(definition);
+            self.leave();
+            let size = size?;
+            self.min_sizes.insert(name.to_string(), size);
+            Ok(size)
+        }
+
+        fn custom_type_min_size(&mut self, custom_type_def: &Value) -> Result<usize, Box<dyn Error>> {
+            let type_kind = custom_type_def.get("type").ok_or("Custom type does not contain 'type'")?;
+            let empty = Vec::new();
+            match type_kind.get("kind").and_then(|k| k.as_str()) {
+                Some("struct") => {
+                    let mut size = 0;
+                    for field in type_kind["fields"].as_array().unwrap_or(&empty) {
+                        size += self.min_size(field.get("type").ok_or("Field has no type")?)?;
+                    }
+                    Ok(size)
                 }
-                Ok(size)
+                Some("enum") => {
+                    let mut smallest: Option<usize> = None;
+                    for variant in type_kind["variants"].as_array().unwrap_or(&empty) {
+                        let mut variant_size = 0;
+                        for field in variant.get("fields").and_then(|f| f.as_array()).unwrap_or(&empty) {
+                            variant_size += self.min_size(field.get("type").unwrap_or(field))?;
+                        }
+                        smallest = Some(smallest.map_or(variant_size, |size| size.min(variant_size)));
+                    }
+                    Ok(1 + smallest.unwrap_or(0)) // 1 byte for the discriminant
+                }
+                _ => Err("Unsupported custom type kind".into()),
             }
-            _ => Err(format!("Unsupported field type: {:?}", field_type).into()),
         }
-    }
 
-    fn calculate_custom_type_size(
-        custom_type_def: &Value,
-        types_map: &HashMap<String, &Value>,
-    ) -> Result<usize, Box<dyn Error>> {
-        let type_kind = custom_type_def
-            .get("type")
-            .ok_or("Custom type does not contain 'type'")?;
+        // Get the size and alignment of a type in a zero-copy layout
+        fn zero_copy_size_align(&mut self, field_type: &Value, packed: bool) -> Result<(usize, usize), Box<dyn Error>> {
+            let (size, align) = match field_type {
+                Value::String(type_str) => match type_str.as_str() {
+                    "u8" | "i8" | "bool" => (1, 1),
+                    "u16" | "i16" => (2, 2),
+                    "u32" | "i32" | "f32" => (4, 4),
+                    "u64" | "i64" | "f64" => (8, 8),
+                    // Programs are built for SBF, which aligns 128-bit integers to 8 bytes
+                    "u128" | "i128" => (16, 8),
+                    "publicKey" | "pubkey" => (32, 1),
+                    "string" | "bytes" => return Err(DynamicSizeError(type_str.clone()).into()),
+                    custom_type if self.types_map.contains_key(custom_type) => {
+                        self.zero_copy_defined_size_align(custom_type, packed)?
+                    }
+                    custom_type => return Err(format!("{} has no zero-copy layout", custom_type).into()),
+                },
+                Value::Object(obj) if obj.get("array").is_some() => {
+                    let item_type = obj["array"].get(0).ok_or("Array type is missing")?;
+                    let count = obj["array"].get(1).and_then(|len| len.as_u64()).ok_or("Array length is invalid")?;
+                    let (size, align) = self.zero_copy_size_align(item_type, packed)?;
+                    (size * count as usize, align)
+                }
+                Value::Object(obj) if obj.get("vec").is_some() || obj.get("option").is_some() => {
+                    return Err(DynamicSizeError(format_idl_type(field_type)).into());
+                }
+                Value::Object(obj) if obj.get("tuple").is_some() => {
+                    let elements = obj["tuple"].as_array().ok_or("Tuple elements must be an array")?;
+                    self.zero_copy_struct_size_align(elements.iter(), packed)?
+                }
+                _ => match defined_type_name(field_type).filter(|name| self.types_map.contains_key(*name)) {
+                    Some(name) => self.zero_copy_defined_size_align(name, packed)?,
+                    None => return Err(format!("{} has no zero-copy layout", format_idl_type(field_type)).into()),
+                },
+            };
+            Ok(if packed { (size, 1) } else { (size, align) })
+        }
 
-        match type_kind.get("kind").and_then(|k| k.as_str()) {
-            Some("struct") => {
-                let fields = type_kind
-                    .get("fields")
-                    .ok_or("Struct type does not contain 'fields'")?
-                    .as_array()
-                    .ok_or("'fields' is not an array")?;
+        // Lay out a defined type once per packing, reusing the result for every later field of that type
+        fn zero_copy_defined_size_align(&mut self, name: &str, packed: bool) -> Result<(usize, usize), Box<dyn Error>> {
+            let key = (name.to_string(), packed);
+            if let Some(size_align) = self.zero_copy_sizes.get(&key) {
+                return Ok(*size_align);
+            }
+            let definition = self.enter(name)?;
+            let size_align = self.zero_copy_custom_size_align(definition, packed);
+            self.leave();
+            let size_align = size_align?;
+            self.zero_copy_sizes.insert(key, size_align);
+            Ok(size_align)
+        }
 
-                let mut size = 0;
-                for field in fields {
-                    let field_type = field.get("type").ok_or("Field has no type")?;
-                    size += calculate_field_size(field_type, types_map)?;
+        // Get the size and alignment of a struct or fieldless enum in a zero-copy layout
+        fn zero_copy_custom_size_align(&mut self, definition: &Value, packed: bool) -> Result<(usize, usize), Box<dyn Error>> {
+            let type_kind = definition.get("type").ok_or("Custom type does not contain 'type'")?;
+            let packed = packed || type_repr_packed(definition);
+            match type_kind.get("kind").and_then(|k| k.as_str()) {
+                Some("struct") => {
+                    let fields = type_kind
+                        .get("fields")
+                        .and_then(|f| f.as_array())
+                        .ok_or("Struct type does not contain 'fields'")?;
+                    let field_types = fields.iter().map(|field| field.get("type").unwrap_or(&Value::Null));
+                    self.zero_copy_struct_size_align(field_types, packed)
                 }
-                Ok(size)
-            }
-            Some("enum") => {
-                let variants = type_kind
-                    .get("variants")
-                    .ok_or("Enum type does not contain 'variants'")?
-                    .as_array()
-                    .ok_or("'variants' is not an array")?;
-
-                // Enums are typically represented as a discriminant (u8) plus the largest variant size
-                let mut max_variant_size = 0;
-                for variant in variants {
-                    let empty_vec = Vec::new();
-                    let variant_fields = variant
-                            .get("fields")
-                            .and_then(|f| f.as_array())
-                            .unwrap_or(&empty_vec);
-
-                    let mut variant_size = 0;
-                    for field in variant_fields {
-                        variant_size += calculate_field_size(field, types_map)?;
+                // Only fieldless enums are plain old data, stored as a single byte tag
+                Some("enum") => {
+                    let variants = type_kind
+                        .get("variants")
+                        .and_then(|v| v.as_array())
+                        .ok_or("Enum type does not contain 'variants'")?;
+                    if variants.iter().any(|variant| variant.get("fields").is_some()) {
+                        return Err("Enums with fields have no zero-copy layout".into());
                     }
-                    max_variant_size = max_variant_size.max(variant_size);
+                    Ok((1, 1))
                 }
-                Ok(1 + max_variant_size) // 1 byte for the discriminant
+                _ => Err("Unsupported custom type kind".into()),
             }
-            _ => Err("Unsupported custom type kind".into()),
+        }
+
+        // Size fields placed one after another with alignment padding, rounding up to the largest alignment
+        fn zero_copy_struct_size_align<'v>(
+            &mut self,
+            field_types: impl Iterator<Item = &'v Value>,
+            packed: bool,
+        ) -> Result<(usize, usize), Box<dyn Error>> {
+            let mut size = 0;
+            let mut struct_align = 1;
+            for field_type in field_types {
+                let (field_size, field_align) = self.zero_copy_size_align(field_type, packed)?;
+                size = align_up(size, field_align) + field_size;
+                struct_align = struct_align.max(field_align);
+            }
+            Ok((align_up(size, struct_align), struct_align))
         }
     }
 
@@ -400,10 +3424,31 @@ pub mod tools {
         }
     }
 
-    /// Encodes a value string into bytes based on the specified type.
+    /// Encodes a value string into bytes based on the specified type, as Borsh does with strings prefixed by their length.
     pub fn encode_value_by_type(value_str: &str, type_str: &str) -> Result<Vec<u8>, Box<dyn Error>> {
         match type_str {
             "u8" => Ok(vec![value_str.parse::<u8>().map_err(|_| "Failed to parse u8")?]),
+            "i8" => Ok(vec![value_str.parse::<i8>().map_err(|_| "Failed to parse i8")? as u8]),
+            "u16" => Ok(value_str
+                .parse::<u16>()
+                .map_err(|_| "Failed to parse u16")?
+                .to_le_bytes()
+                .to_vec()),
+            "i16" => Ok(value_str
+                .parse::<i16>()
+                .map_err(|_| "Failed to parse i16")?
+                .to_le_bytes()
+                .to_vec()),
+            "u32" => Ok(value_str
+                .parse::<u32>()
+                .map_err(|_| "Failed to parse u32")?
+                .to_le_bytes()
+                .to_vec()),
+            "i32" => Ok(value_str
+                .parse::<i32>()
+                .map_err(|_| "Failed to parse i32")?
+                .to_le_bytes()
+                .to_vec()),
             "u64" => Ok(value_str
                 .parse::<u64>()
                 .map_err(|_| "Failed to parse u64")?
@@ -414,16 +3459,36 @@ pub mod tools {
                 .map_err(|_| "Failed to parse i64")?
                 .to_le_bytes()
                 .to_vec()),
+            "u128" => Ok(value_str
+                .parse::<u128>()
+                .map_err(|_| "Failed to parse u128")?
+                .to_le_bytes()
+                .to_vec()),
+            "i128" => Ok(value_str
+                .parse::<i128>()
+                .map_err(|_| "Failed to parse i128")?
+                .to_le_bytes()
+                .to_vec()),
+            "f32" => Ok(value_str
+                .parse::<f32>()
+                .map_err(|_| "Failed to parse f32")?
+                .to_le_bytes()
+                .to_vec()),
             "f64" => Ok(value_str
                 .parse::<f64>()
                 .map_err(|_| "Failed to parse f64")?
                 .to_le_bytes()
                 .to_vec()),
             "bool" => Ok(vec![value_str.parse::<bool>().map_err(|_| "Failed to parse bool")? as u8]),
-            "publicKey" => Ok(Pubkey::from_str(value_str)
+            "publicKey" | "pubkey" => Ok(Pubkey::from_str(value_str)
                 .map_err(|_| "Failed to parse publicKey")?
                 .to_bytes()
                 .to_vec()),
+            "string" => {
+                let mut encoded = (value_str.len() as u32).to_le_bytes().to_vec();
+                encoded.extend_from_slice(value_str.as_bytes());
+                Ok(encoded)
+            }
             _ => Err(format!("Unsupported variable type: {}", type_str).into()),
         }
     }
@@ -449,62 +3514,657 @@ pub mod tools {
     pub fn get_variable_type_from_idl(
         idl: &str,
         account_name: &str,
-        variable_path: &str,
-    ) -> Result<String, Box<dyn Error>> {
-        let idl_json: Value = serde_json::from_str(idl)?;
-        let account = find_account_in_idl(&idl_json, account_name)?;
-        let mut current_fields = extract_account_fields(account)?;
+        variable_path: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let account = find_account_definition(&idl_json, account_name)?;
+        let mut current_fields = extract_account_fields(account)?;
+
+        for part in variable_path.split('.') {
+            let field = current_fields
+                .iter()
+                .find(|f| f.get("name").map_or(false, |name| name == part))
+                .ok_or(format!("Field '{}' not found", part))?;
+
+            if part == variable_path.split('.').last().unwrap() {
+                return field
+                    .get("type")
+                    .and_then(|t| t.as_str())
+                    .map(String::from)
+                    .ok_or("Field type not found".into());
+            }
+
+            current_fields = field
+                .get("type")
+                .and_then(|t| t.get("fields"))
+                .ok_or("Field type does not contain 'fields'")?
+                .as_array()
+                .ok_or("'fields' is not an array")?;
+        }
+
+        Err("Variable type not found".into())
+    }
+
+    /// Decodes the full contents of an account into a JSON object based on its IDL definition.
+    pub fn decode_account_data(idl: &str, account_name: &str, data: &[u8]) -> Result<Value, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json)?;
+        let account = find_account_definition(&idl_json, account_name)?;
+        let fields = extract_account_fields(account)?;
+
+        let discriminator = data
+            .get(..DISCRIMINATOR_LEN)
+            .ok_or("Account data is shorter than the discriminator")?;
+        if Some(discriminator.to_vec()) != lookup_account_discriminator(&idl_json, account_name) {
+            return Err(format!("Account data is not a '{}' account", account_name).into());
+        }
+
+        let (decoded, _) = decode_struct_fields(data, DISCRIMINATOR_LEN, fields, &mut TypeResolver::new(&types_map))?;
+        Ok(Value::Object(decoded))
+    }
+
+    /// Lists the flattened path and IDL type of every leaf field in an account, descending into nested structs.
+    pub fn get_account_field_paths(idl: &str, account_name: &str) -> Result<Vec<(String, Value)>, Box<dyn Error>> {
+        let idl_json = parse_idl(idl)?;
+        let types_map = build_types_map(&idl_json)?;
+        let account = find_account_definition(&idl_json, account_name)?;
+
+        let mut paths = Vec::new();
+        collect_field_paths("", extract_account_fields(account)?, &mut TypeResolver::new(&types_map), &mut paths)?;
+        Ok(paths)
+    }
+
+    // Helper to walk struct fields and collect their leaf paths
+    fn collect_field_paths(
+        prefix: &str,
+        fields: &[Value],
+        resolver: &mut TypeResolver,
+        paths: &mut Vec<(String, Value)>,
+    ) -> Result<(), Box<dyn Error>> {
+        for field in fields {
+            let field_name = field
+                .get("name")
+                .and_then(|n| n.as_str())
+                .ok_or("Field has no name")?;
+            let field_type = field.get("type").ok_or("Field has no type")?;
+            let path = if prefix.is_empty() {
+                field_name.to_string()
+            } else {
+                format!("{}.{}", prefix, field_name)
+            };
+
+            match resolve_nested_fields(field_type, resolver.types_map) {
+                Ok(nested_fields) => {
+                    resolver.enter(defined_type_name(field_type).unwrap_or_default())?;
+                    let nested = collect_field_paths(&path, nested_fields, resolver, paths);
+                    resolver.leave();
+                    nested?;
+                }
+                Err(_) => paths.push((path, field_type.clone())),
+            }
+        }
+        Ok(())
+    }
+
+    // Helper to get the name of a defined type in either the legacy or the current IDL format
+    fn defined_type_name(field_type: &Value) -> Option<&str> {
+        let defined = field_type.get("defined")?;
+        defined
+            .as_str()
+            .or_else(|| defined.get("name").and_then(|n| n.as_str()))
+    }
+
+    // Helper to read a fixed number of bytes for a type
+    fn read_bytes<'a>(data: &'a [u8], offset: usize, len: usize, type_str: &str) -> Result<&'a [u8], Box<dyn Error>> {
+        data.get(offset..offset + len)
+            .ok_or_else(|| format!("Failed to extract {} value at offset {}", type_str, offset).into())
+    }
+
+    // Helper to read a Borsh u32 length prefix
+    fn read_len(data: &[u8], offset: usize) -> Result<usize, Box<dyn Error>> {
+        let len_bytes = read_bytes(data, offset, 4, "length prefix")?;
+        Ok(u32::from_le_bytes(len_bytes.try_into()?) as usize)
+    }
+
+    // Decode consecutive struct fields into a JSON map, returning the map and the bytes consumed
+    fn decode_struct_fields(
+        data: &[u8],
+        offset: usize,
+        fields: &[Value],
+        resolver: &mut TypeResolver,
+    ) -> Result<(serde_json::Map<String, Value>, usize), Box<dyn Error>> {
+        let mut decoded = serde_json::Map::new();
+        let mut cursor = offset;
+        for field in fields {
+            let field_name = field
+                .get("name")
+                .and_then(|n| n.as_str())
+                .ok_or("Field has no name")?;
+            let field_type = field.get("type").ok_or("Field has no type")?;
+            let (value, size) = decode_idl_value(data, cursor, field_type, resolver)?;
+            decoded.insert(field_name.to_string(), value);
+            cursor += size;
+        }
+        Ok((decoded, cursor - offset))
+    }
+
+    // Decode a Borsh-encoded value of an IDL type, returning the value and the bytes consumed
+    fn decode_idl_value(
+        data: &[u8],
+        offset: usize,
+        field_type: &Value,
+        resolver: &mut TypeResolver,
+    ) -> Result<(Value, usize), Box<dyn Error>> {
+        match field_type {
+            Value::String(type_str) => match type_str.as_str() {
+                "u8" => Ok((read_bytes(data, offset, 1, type_str)?[0].into(), 1)),
+                "i8" => Ok(((read_bytes(data, offset, 1, type_str)?[0] as i8).into(), 1)),
+                "u16" => Ok((u16::from_le_bytes(read_bytes(data, offset, 2, type_str)?.try_into()?).into(), 2)),
+                "i16" => Ok((i16::from_le_bytes(read_bytes(data, offset, 2, type_str)?.try_into()?).into(), 2)),
+                "u32" => Ok((u32::from_le_bytes(read_bytes(data, offset, 4, type_str)?.try_into()?).into(), 4)),
+                "i32" => Ok((i32::from_le_bytes(read_bytes(data, offset, 4, type_str)?.try_into()?).into(), 4)),
+                "f32" => Ok((f32::from_le_bytes(read_bytes(data, offset, 4, type_str)?.try_into()?).into(), 4)),
+                "u64" => Ok((u64::from_le_bytes(read_bytes(data, offset, 8, type_str)?.try_into()?).into(), 8)),
+                "i64" => Ok((i64::from_le_bytes(read_bytes(data, offset, 8, type_str)?.try_into()?).into(), 8)),
+                "f64" => Ok((f64::from_le_bytes(read_bytes(data, offset, 8, type_str)?.try_into()?).into(), 8)),
+                // JSON numbers cannot hold 128-bit integers, so these are kept as strings
+                "u128" => Ok((u128::from_le_bytes(read_bytes(data, offset, 16, type_str)?.try_into()?).to_string().into(), 16)),
+                "i128" => Ok((i128::from_le_bytes(read_bytes(data, offset, 16, type_str)?.try_into()?).to_string().into(), 16)),
+                "bool" => Ok(((read_bytes(data, offset, 1, type_str)?[0] != 0).into(), 1)),
+                "publicKey" | "pubkey" => {
+                    let key_bytes: [u8; 32] = read_bytes(data, offset, 32, type_str)?.try_into()?;
+                    Ok((Pubkey::new_from_array(key_bytes).to_string().into(), 32))
+                }
+                "string" => {
+                    let len = read_len(data, offset)?;
+                    let string_bytes = read_bytes(data, offset + 4, len, type_str)?;
+                    Ok((String::from_utf8(string_bytes.to_vec())?.into(), 4 + len))
+                }
+                "bytes" => {
+                    let len = read_len(data, offset)?;
+                    let raw = read_bytes(data, offset + 4, len, type_str)?;
+                    Ok((BASE64_STANDARD.encode(raw).into(), 4 + len))
+                }
+                custom_type => decode_defined_type(data, offset, custom_type, resolver),
+            },
+            Value::Object(obj) if obj.get("array").is_some() => {
+                let array = obj.get("array").ok_or("Array type is invalid")?;
+                let array_type = array.get(0).ok_or("Array type is missing")?;
+                let array_length = array
+                    .get(1)
+                    .and_then(|len| len.as_u64())
+                    .ok_or("Array length is invalid")?;
+                decode_sequence(data, offset, array_type, array_length as usize, resolver)
+            }
+            Value::Object(obj) if obj.get("vec").is_some() => {
+                let vec_type = obj.get("vec").ok_or("Vec type is invalid")?;
+                let len = read_len(data, offset)?;
+                let remaining = data.len().saturating_sub(offset + 4);
+                if len.saturating_mul(min_encoded_size(vec_type, resolver)) > remaining {
+                    return Err(format!("Vec length {} at offset {} exceeds the {} remaining bytes", len, offset, remaining).into());
+                }
+                let (items, size) = decode_sequence(data, offset + 4, vec_type, len, resolver)?;
+                Ok((items, 4 + size))
+            }
+            Value::Object(obj) if obj.get("option").is_some() => {
+                let option_type = obj.get("option").ok_or("Option type is invalid")?;
+                match read_bytes(data, offset, 1, "option tag")?[0] {
+                    0 => Ok((Value::Null, 1)),
+                    1 => {
+                        let (value, size) = decode_idl_value(data, offset + 1, option_type, resolver)?;
+                        Ok((value, 1 + size))
+                    }
+                    tag => Err(format!("Invalid option tag {} at offset {}", tag, offset).into()),
+                }
+            }
+            Value::Object(obj) if obj.get("coption").is_some() => {
+                // COption always occupies a 4-byte tag plus the inner type, even when empty
+                let coption_type = obj.get("coption").ok_or("COption type is invalid")?;
+                let tag = read_len(data, offset)?;
+                let (value, size) = decode_idl_value(data, offset + 4, coption_type, resolver)?;
+                match tag {
+                    0 => Ok((Value::Null, 4 + size)),
+                    1 => Ok((value, 4 + size)),
+                    tag => Err(format!("Invalid coption tag {} at offset {}", tag, offset).into()),
+                }
+            }
+            Value::Object(obj) if obj.get("tuple").is_some() => {
+                let tuple_elements = obj
+                    .get("tuple")
+                    .and_then(|t| t.as_array())
+                    .ok_or("Tuple elements must be an array")?;
+                let mut items = Vec::new();
+                let mut size = 0;
+                for element in tuple_elements {
+                    let (value, element_size) = decode_idl_value(data, offset + size, element, resolver)?;
+                    items.push(value);
+                    size += element_size;
+                }
+                Ok((Value::Array(items), size))
+            }
+            Value::Object(_) if defined_type_name(field_type).is_some() => {
+                let defined_type = defined_type_name(field_type).ok_or("Invalid 'defined' type")?;
+                decode_defined_type(data, offset, defined_type, resolver)
+            }
+            _ => Err(format!("Unsupported field type: {:?}", field_type).into()),
+        }
+    }
+
+    // Helper to get the fewest bytes a value of a type can take, counting at least one so lengths stay bounded
+    fn min_encoded_size(item_type: &Value, resolver: &mut TypeResolver) -> usize {
+        resolver.min_size(item_type).unwrap_or(1).max(1)
+    }
+
+    // Decode a fixed number of consecutive values of the same type into a JSON array
+    fn decode_sequence(
+        data: &[u8],
+        offset: usize,
+        item_type: &Value,
+        count: usize,
+        resolver: &mut TypeResolver,
+    ) -> Result<(Value, usize), Box<dyn Error>> {
+        let mut items = Vec::with_capacity(count.min(data.len()));
+        let mut size = 0;
+        for _ in 0..count {
+            let (value, item_size) = decode_idl_value(data, offset + size, item_type, resolver)?;
+            items.push(value);
+            size += item_size;
+        }
+        Ok((Value::Array(items), size))
+    }
+
+    // Decode a named type from the IDL 'types' section, guarding against recursive definitions
+    fn decode_defined_type(
+        data: &[u8],
+        offset: usize,
+        name: &str,
+        resolver: &mut TypeResolver,
+    ) -> Result<(Value, usize), Box<dyn Error>> {
+        let custom_type_def = resolver.enter_at(name, Some(offset))?;
+        let decoded = decode_custom_type(data, offset, custom_type_def, resolver);
+        resolver.leave();
+        decoded
+    }
+
+    // Decode a struct or enum defined in the IDL 'types' section
+    fn decode_custom_type(
+        data: &[u8],
+        offset: usize,
+        custom_type_def: &Value,
+        resolver: &mut TypeResolver,
+    ) -> Result<(Value, usize), Box<dyn Error>> {
+        let type_kind = custom_type_def
+            .get("type")
+            .ok_or("Custom type does not contain 'type'")?;
+
+        match type_kind.get("kind").and_then(|k| k.as_str()) {
+            Some("struct") => {
+                let fields = type_kind
+                    .get("fields")
+                    .and_then(|f| f.as_array())
+                    .ok_or("Struct type does not contain 'fields'")?;
+                let (decoded, size) = decode_struct_fields(data, offset, fields, resolver)?;
+                Ok((Value::Object(decoded), size))
+            }
+            Some("enum") => {
+                let variants = type_kind
+                    .get("variants")
+                    .and_then(|v| v.as_array())
+                    .ok_or("Enum type does not contain 'variants'")?;
+                let tag = read_bytes(data, offset, 1, "enum tag")?[0];
+                let variant = variants
+                    .get(tag as usize)
+                    .ok_or_else(|| format!("Invalid enum tag {} at offset {}", tag, offset))?;
+                let variant_name = variant
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .ok_or("Enum variant has no name")?;
+
+                let variant_fields = match variant.get("fields").and_then(|f| f.as_array()) {
+                    Some(fields) if !fields.is_empty() => fields,
+                    _ => return Ok((variant_name.into(), 1)),
+                };
+
+                // Named variant fields decode to an object, tuple variant fields to an array
+                let (value, size) = if variant_fields[0].get("name").is_some() {
+                    let (decoded, size) = decode_struct_fields(data, offset + 1, variant_fields, resolver)?;
+                    (Value::Object(decoded), size)
+                } else {
+                    let mut items = Vec::new();
+                    let mut size = 0;
+                    for field_type in variant_fields {
+                        let (value, field_size) = decode_idl_value(data, offset + 1 + size, field_type, resolver)?;
+                        items.push(value);
+                        size += field_size;
+                    }
+                    (Value::Array(items), size)
+                };
+                Ok((serde_json::json!({ variant_name: value }), 1 + size))
+            }
+            _ => Err("Unsupported custom type kind".into()),
+        }
+    }
+
+    // Borsh-encode a JSON value according to its IDL type, the inverse of decode_idl_value
+    fn encode_idl_value(
+        value: &Value,
+        field_type: &Value,
+        resolver: &mut TypeResolver,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Box<dyn Error>> {
+        match field_type {
+            Value::String(type_str) => match type_str.as_str() {
+                "bytes" => {
+                    let bytes = match value {
+                        Value::String(encoded) => BASE64_STANDARD.decode(encoded)?,
+                        Value::Array(items) => items
+                            .iter()
+                            .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                            .collect::<Option<Vec<u8>>>()
+                            .ok_or("Bytes must be base64 or an array of bytes")?,
+                        _ => return Err("Bytes must be base64 or an array of bytes".into()),
+                    };
+                    out.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+                    out.extend_from_slice(&bytes);
+                    Ok(())
+                }
+                primitive if !resolver.types_map.contains_key(primitive) => {
+                    let value_str = match value {
+                        Value::String(s) => s.clone(),
+                        Value::Number(_) | Value::Bool(_) => value.to_string(),
+                        _ => return Err(format!("Expected a {} value, got {}", primitive, value).into()),
+                    };
+                    out.extend(encode_value_by_type(&value_str, primitive)?);
+                    Ok(())
+                }
+                custom_type => encode_defined_type(value, custom_type, resolver, out),
+            },
+            Value::Object(obj) if obj.get("array").is_some() => {
+                let array = obj.get("array").ok_or("Array type is invalid")?;
+                let array_type = array.get(0).ok_or("Array type is missing")?;
+                let array_length = array
+                    .get(1)
+                    .and_then(|len| len.as_u64())
+                    .ok_or("Array length is invalid")?;
+                let items = value.as_array().ok_or("Expected an array")?;
+                if items.len() as u64 != array_length {
+                    return Err(format!("Expected {} array items, got {}", array_length, items.len()).into());
+                }
+                items
+                    .iter()
+                    .try_for_each(|item| encode_idl_value(item, array_type, resolver, out))
+            }
+            Value::Object(obj) if obj.get("vec").is_some() => {
+                let vec_type = obj.get("vec").ok_or("Vec type is invalid")?;
+                let items = value.as_array().ok_or("Expected an array")?;
+                out.extend_from_slice(&(items.len() as u32).to_le_bytes());
+                items
+                    .iter()
+                    .try_for_each(|item| encode_idl_value(item, vec_type, resolver, out))
+            }
+            Value::Object(obj) if obj.get("option").is_some() => {
+                let option_type = obj.get("option").ok_or("Option type is invalid")?;
+                if value.is_null() {
+                    out.push(0);
+                    return Ok(());
+                }
+                out.push(1);
+                encode_idl_value(value, option_type, resolver, out)
+            }
+            Value::Object(obj) if obj.get("coption").is_some() => {
+                // COption always occupies a 4-byte tag plus the inner type, even when empty
+                let coption_type = obj.get("coption").ok_or("COption type is invalid")?;
+                if value.is_null() {
+                    out.extend_from_slice(&0u32.to_le_bytes());
+                    out.extend(vec![0; resolver.field_size(coption_type)?]);
+                    return Ok(());
+                }
+                out.extend_from_slice(&1u32.to_le_bytes());
+                encode_idl_value(value, coption_type, resolver, out)
+            }
+            Value::Object(obj) if obj.get("tuple").is_some() => {
+                let tuple_elements = obj
+                    .get("tuple")
+                    .and_then(|t| t.as_array())
+                    .ok_or("Tuple elements must be an array")?;
+                let items = value.as_array().ok_or("Expected an array for tuple")?;
+                if items.len() != tuple_elements.len() {
+                    return Err(format!("Expected {} tuple items, got {}", tuple_elements.len(), items.len()).into());
+                }
+                items
+                    .iter()
+                    .zip(tuple_elements)
+                    .try_for_each(|(item, element)| encode_idl_value(item, element, resolver, out))
+            }
+            Value::Object(_) if defined_type_name(field_type).is_some() => {
+                let defined_type = defined_type_name(field_type).ok_or("Invalid 'defined' type")?;
+                encode_defined_type(value, defined_type, resolver, out)
+            }
+            _ => Err(format!("Unsupported field type: {:?}", field_type).into()),
+        }
+    }
+
+    // Encode a named type from the IDL 'types' section, guarding against recursive definitions
+    fn encode_defined_type(
+        value: &Value,
+        name: &str,
+        resolver: &mut TypeResolver,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Box<dyn Error>> {
+        let custom_type_def = resolver.enter_at(name, Some(out.len()))?;
+        let encoded = encode_custom_type(value, custom_type_def, resolver, out);
+        resolver.leave();
+        encoded
+    }
+
+    // Encode named struct fields from a JSON object in IDL order
+    fn encode_struct_fields(
+        value: &Value,
+        fields: &[Value],
+        resolver: &mut TypeResolver,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Box<dyn Error>> {
+        for field in fields {
+            let name = field.get("name").and_then(|n| n.as_str()).ok_or("Field has no name")?;
+            let field_type = field.get("type").ok_or("Field has no type")?;
+            let field_value = value
+                .get(name)
+                .ok_or_else(|| format!("Missing field: {}", name))?;
+            encode_idl_value(field_value, field_type, resolver, out)?;
+        }
+        Ok(())
+    }
+
+    // Encode a struct or enum defined in the IDL 'types' section, the inverse of decode_custom_type
+    fn encode_custom_type(
+        value: &Value,
+        custom_type_def: &Value,
+        resolver: &mut TypeResolver,
+        out: &mut Vec<u8>,
+    ) -> Result<(), Box<dyn Error>> {
+        let type_kind = custom_type_def
+            .get("type")
+            .ok_or("Custom type does not contain 'type'")?;
+
+        match type_kind.get("kind").and_then(|k| k.as_str()) {
+            Some("struct") => {
+                let fields = type_kind
+                    .get("fields")
+                    .and_then(|f| f.as_array())
+                    .ok_or("Struct type does not contain 'fields'")?;
+                encode_struct_fields(value, fields, resolver, out)
+            }
+            Some("enum") => {
+                let variants = type_kind
+                    .get("variants")
+                    .and_then(|v| v.as_array())
+                    .ok_or("Enum type does not contain 'variants'")?;
+                // Unit variants are given by name, variants with fields as {"Variant": fields}
+                let (variant_name, variant_value) = match value {
+                    Value::String(name) => (name.as_str(), None),
+                    Value::Object(obj) if obj.len() == 1 => {
+                        let (name, fields) = obj.iter().next().ok_or("Enum value is empty")?;
+                        (name.as_str(), Some(fields))
+                    }
+                    _ => return Err(format!("Invalid enum value: {}", value).into()),
+                };
+                let tag = variants
+                    .iter()
+                    .position(|v| v.get("name").and_then(|n| n.as_str()) == Some(variant_name))
+                    .ok_or_else(|| format!("Unknown enum variant: {}", variant_name))?;
+                out.push(tag as u8);
+
+                let variant_fields = match variants[tag].get("fields").and_then(|f| f.as_array()) {
+                    Some(fields) if !fields.is_empty() => fields,
+                    _ => return Ok(()),
+                };
+                let variant_value =
+                    variant_value.ok_or_else(|| format!("Enum variant {} requires fields", variant_name))?;
+                if variant_fields[0].get("name").is_some() {
+                    encode_struct_fields(variant_value, variant_fields, resolver, out)
+                } else {
+                    let items = variant_value.as_array().ok_or("Expected an array for tuple variant")?;
+                    if items.len() != variant_fields.len() {
+                        return Err(format!("Enum variant {} expects {} fields", variant_name, variant_fields.len()).into());
+                    }
+                    items
+                        .iter()
+                        .zip(variant_fields)
+                        .try_for_each(|(item, field_type)| encode_idl_value(item, field_type, resolver, out))
+                }
+            }
+            _ => Err("Unsupported custom type kind".into()),
+        }
+    }
+
+    /// A single decoded field whose value differs between two versions of an account.
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct FieldChange {
+        pub path: String,
+        pub old: Value,
+        pub new: Value,
+    }
+
+    impl fmt::Display for FieldChange {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{}: {} -> {}", self.path, display_value(&self.old), display_value(&self.new))
+        }
+    }
+
+    /// Formats a decoded value for display, without quoting plain strings.
+    pub fn display_value(value: &Value) -> String {
+        match value {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Compares two versions of an account and returns every decoded field path whose value changed.
+    pub fn diff_account_data(
+        idl: &str,
+        account_name: &str,
+        old_data: &[u8],
+        new_data: &[u8],
+    ) -> Result<Vec<FieldChange>, Box<dyn Error>> {
+        let old_value = decode_account_data(idl, account_name, old_data)?;
+        let new_value = decode_account_data(idl, account_name, new_data)?;
 
-        for part in variable_path.split('.') {
-            let field = current_fields
-                .iter()
-                .find(|f| f.get("name").map_or(false, |name| name == part))
-                .ok_or(format!("Field '{}' not found", part))?;
+        let mut changes = Vec::new();
+        collect_value_changes("", &old_value, &new_value, &mut changes);
+        Ok(changes)
+    }
 
-            if part == variable_path.split('.').last().unwrap() {
-                return field
-                    .get("type")
-                    .and_then(|t| t.as_str())
-                    .map(String::from)
-                    .ok_or("Field type not found".into());
+    // Helper to walk two decoded values in parallel and record the leaves that differ
+    fn collect_value_changes(path: &str, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+        match (old, new) {
+            // Objects with other keys, such as an enum that switched variant, change as a whole
+            (Value::Object(old_fields), Value::Object(new_fields))
+                if old_fields.len() == new_fields.len() && old_fields.keys().all(|name| new_fields.contains_key(name)) =>
+            {
+                for (name, old_field) in old_fields {
+                    let field_path = if path.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", path, name)
+                    };
+                    collect_value_changes(&field_path, old_field, &new_fields[name], changes);
+                }
             }
-
-            current_fields = field
-                .get("type")
-                .and_then(|t| t.get("fields"))
-                .ok_or("Field type does not contain 'fields'")?
-                .as_array()
-                .ok_or("'fields' is not an array")?;
+            (Value::Array(old_items), Value::Array(new_items)) if old_items.len() == new_items.len() => {
+                for (i, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                    collect_value_changes(&format!("{}[{}]", path, i), old_item, new_item, changes);
+                }
+            }
+            _ if old != new => changes.push(FieldChange {
+                path: path.to_string(),
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            _ => {}
         }
-
-        Err("Variable type not found".into())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::fs;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
 
     use crate::tools::{calculate_discriminator, get_program_accounts_with_discrim};
 
     use super::tools;
     use base64::prelude::*;
-    use solana_client::rpc_client::RpcClient;
+    use serde_json::{json, Value};
+    use sha2::{Digest, Sha256};
+    use solana_client::{
+        rpc_client::{Mocks, RpcClient},
+        rpc_request::RpcRequest,
+    };
     use solana_sdk::{
-        message::Message, pubkey::Pubkey, signature::Keypair, signer::Signer,
+        account::Account,
+        address_lookup_table::AddressLookupTableAccount,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::{v0, Message, VersionedMessage},
+        pubkey::Pubkey,
+        signature::Keypair,
+        signer::Signer,
         transaction::Transaction,
     };
+    use tungstenite::Message as WsMessage;
 
     #[test]
     fn test_decode_transaction() {
         let payer = Keypair::new();
+        let recipient = Pubkey::new_unique();
         let ix =
-            solana_sdk::system_instruction::transfer(&payer.pubkey(), &Pubkey::new_unique(), 10);
+            solana_sdk::system_instruction::transfer(&payer.pubkey(), &recipient, 10);
         let message = Message::new(&[ix], Some(&(payer.pubkey())));
         let tx: Transaction = Transaction::new_unsigned(message);
         let message_data = BASE64_STANDARD.encode(tx.message_data());
         println!("Message: {}", message_data);
-        super::tools::decode_message(&message_data);
+
+        let decoded = super::tools::decode_message(&message_data).expect("Failed to decode message");
+        println!("{}", decoded);
+        assert_eq!(decoded.header.num_required_signatures, 1);
+        assert_eq!(decoded.account_keys.len(), 3);
+        assert!(decoded.account_keys[0].signer && decoded.account_keys[0].writable);
+        assert!(!decoded.account_keys[1].signer && decoded.account_keys[1].writable);
+        assert!(!decoded.account_keys[2].writable);
+
+        let instruction = &decoded.instructions[0];
+        assert_eq!(instruction.program_id, solana_sdk::system_program::id().to_string());
+        assert_eq!(instruction.accounts[1].pubkey, recipient.to_string());
+
+        let json = serde_json::to_value(&decoded).unwrap();
+        assert_eq!(json["instructions"][0]["data"], BASE64_STANDARD.encode(&instruction.data));
+
+        assert!(super::tools::decode_message("not a message").is_err());
     }
 
     #[test]
@@ -579,4 +4239,1349 @@ mod tests {
         println!("Discriminator for '{}': {:?}", account_name, discriminator);
         assert_eq!(discriminator.len(), 8);
     }
+
+    #[test]
+    fn test_find_account_discriminator() {
+        let idl = r#"{
+            "accounts": [
+                {"name": "Pool", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]},
+                {"name": "Position"},
+                {"name": "Broken", "discriminator": [1, 2, 3, 4, 5, 6, 7, 256]}
+            ],
+            "types": []
+        }"#;
+        assert_eq!(tools::find_account_discriminator(idl, "Pool").unwrap(), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(tools::find_account_discriminator(idl, "Position").unwrap(), calculate_discriminator("Position"));
+        assert!(tools::find_account_discriminator(idl, "Missing").is_err());
+        // Bytes out of range are rejected rather than wrapped
+        assert!(tools::find_account_discriminator(idl, "Broken").is_err());
+    }
+
+    #[test]
+    fn test_account_layout_from_types() {
+        // Newer IDLs keep only the name and discriminator under 'accounts'
+        let idl = r#"{
+            "accounts": [{"name": "Pool", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]}, {"name": "Orphan"}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [
+                {"name": "bump", "type": "u8"},
+                {"name": "liquidity", "type": "u64"}
+            ]}}]
+        }"#;
+        assert_eq!(tools::get_variable_offset_from_idl(idl, "Pool", "liquidity").unwrap(), 9);
+        assert_eq!(tools::get_variable_type_from_idl(idl, "Pool", "liquidity").unwrap(), "u64");
+
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8, 254];
+        data.extend_from_slice(&1000u64.to_le_bytes());
+        assert_eq!(
+            tools::decode_account_data(idl, "Pool", &data).unwrap(),
+            serde_json::json!({"bump": 254, "liquidity": 1000})
+        );
+
+        // Without a type of the same name there is no layout to fall back to
+        assert!(tools::get_account_layout(idl, "Orphan").is_err());
+    }
+
+    #[test]
+    fn test_decode_account_data() {
+        let idl = r#"{
+            "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [
+                {"name": "owner", "type": "publicKey"},
+                {"name": "amount", "type": "u64"},
+                {"name": "config", "type": {"defined": "Config"}},
+                {"name": "label", "type": "string"},
+                {"name": "side", "type": {"defined": "Side"}},
+                {"name": "limit", "type": {"option": "u16"}}
+            ]}}],
+            "types": [
+                {"name": "Config", "type": {"kind": "struct", "fields": [
+                    {"name": "fee", "type": "u16"},
+                    {"name": "enabled", "type": "bool"}
+                ]}},
+                {"name": "Side", "type": {"kind": "enum", "variants": [{"name": "Long"}, {"name": "Short"}]}}
+            ]
+        }"#;
+        let owner = Pubkey::new_unique();
+        let mut data = tools::calculate_discriminator("Vault").to_vec();
+        data.extend_from_slice(&owner.to_bytes());
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(&30u16.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(b"abc");
+        data.extend_from_slice(&[1, 0]);
+
+        let decoded = tools::decode_account_data(idl, "Vault", &data).expect("Failed to decode account");
+        assert_eq!(decoded["owner"], owner.to_string());
+        assert_eq!(decoded["amount"], 42);
+        assert_eq!(decoded["config"]["fee"], 30);
+        assert_eq!(decoded["config"]["enabled"], true);
+        assert_eq!(decoded["label"], "abc");
+        assert_eq!(decoded["side"], "Short");
+        assert!(decoded["limit"].is_null());
+
+        let paths = tools::get_account_field_paths(idl, "Vault").expect("Failed to get field paths");
+        let names: Vec<&str> = paths.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(names, ["owner", "amount", "config.fee", "config.enabled", "label", "side", "limit"]);
+    }
+
+    #[test]
+    fn test_diff_account_data() {
+        let idl = r#"{"accounts": [{"name": "Counter", "type": {"kind": "struct", "fields": [
+            {"name": "count", "type": "u64"},
+            {"name": "bump", "type": "u8"}
+        ]}}], "types": []}"#;
+        let counter = |count: u64| {
+            let mut data = tools::calculate_discriminator("Counter").to_vec();
+            data.extend_from_slice(&count.to_le_bytes());
+            data.push(254);
+            data
+        };
+        let (old_data, new_data) = (counter(42), counter(50));
+
+        let changes = tools::diff_account_data(idl, "Counter", &old_data, &new_data).expect("Failed to diff accounts");
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to_string(), "count: 42 -> 50");
+
+        let unchanged = tools::diff_account_data(idl, "Counter", &old_data, &old_data).expect("Failed to diff accounts");
+        assert!(unchanged.is_empty());
+
+        // A variant switch is one change of the whole enum, showing the new variant
+        let idl = r#"{
+            "accounts": [{"name": "Market", "type": {"kind": "struct", "fields": [{"name": "state", "type": {"defined": "State"}}]}}],
+            "types": [{"name": "State", "type": {"kind": "enum", "variants": [
+                {"name": "Active", "fields": [{"name": "since", "type": "u8"}]},
+                {"name": "Closed", "fields": [{"name": "at", "type": "u8"}]}
+            ]}}]
+        }"#;
+        let market = |variant: u8| [&tools::calculate_discriminator("Market")[..], &[variant, 7]].concat();
+        let changes = tools::diff_account_data(idl, "Market", &market(0), &market(1)).unwrap();
+        let changes: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+        assert_eq!(changes, [r#"state: {"Active":{"since":7}} -> {"Closed":{"at":7}}"#]);
+    }
+
+    #[test]
+    fn test_subscribe_program_accounts_with_discrim() {
+        let program = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let idl = r#"{"accounts": [{"name": "Counter", "type": {"kind": "struct", "fields": [
+            {"name": "count", "type": "u64"}
+        ]}}], "types": []}"#;
+        let mut data = tools::calculate_discriminator("Counter").to_vec();
+        data.extend_from_slice(&7u64.to_le_bytes());
+
+        // Local websocket stand-in that answers one programSubscribe and pushes a single notification
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let notification_data = BASE64_STANDARD.encode(&data);
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            let request: Value = serde_json::from_str(&socket.read().unwrap().into_text().unwrap()).unwrap();
+            assert_eq!(request["method"], "programSubscribe");
+            assert_eq!(request["params"][1]["filters"].as_array().unwrap().len(), 2);
+
+            socket
+                .send(WsMessage::Text(json!({"jsonrpc": "2.0", "result": 1, "id": 1}).to_string()))
+                .unwrap();
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "programNotification",
+                "params": {
+                    "subscription": 1,
+                    "result": {
+                        "context": {"slot": 10},
+                        "value": {
+                            "pubkey": account.to_string(),
+                            "account": {
+                                "data": [notification_data, "base64"],
+                                "executable": false,
+                                "lamports": 1,
+                                "owner": program.to_string(),
+                                "rentEpoch": 0,
+                                "space": data.len()
+                            }
+                        }
+                    }
+                }
+            });
+            socket.send(WsMessage::Text(notification.to_string())).unwrap();
+            socket.close(None).unwrap();
+        });
+
+        let (_subscription, receiver) = tools::subscribe_program_accounts_with_discrim(
+            &format!("ws://127.0.0.1:{}", port),
+            &program.to_string(),
+            &calculate_discriminator("Counter"),
+            &[(8, 7u64.to_le_bytes().to_vec())],
+        )
+        .expect("Failed to subscribe");
+
+        let update = receiver.recv_timeout(Duration::from_secs(5)).expect("No update received");
+        assert_eq!(update.context.slot, 10);
+        assert_eq!(update.value.pubkey, account.to_string());
+        let decoded_account: Account = update.value.account.decode().unwrap();
+        let decoded = tools::decode_account_data(idl, "Counter", &decoded_account.data).unwrap();
+        assert_eq!(decoded["count"], 7);
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_get_program_accounts_with_slot() {
+        let program = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let mut data = calculate_discriminator("Counter").to_vec();
+        data.extend_from_slice(&9u64.to_le_bytes());
+
+        let mut mocks = Mocks::new();
+        mocks.insert(
+            RpcRequest::GetProgramAccounts,
+            json!({
+                "context": {"slot": 321},
+                "value": [{
+                    "pubkey": account.to_string(),
+                    "account": {
+                        "data": [BASE64_STANDARD.encode(&data), "base64"],
+                        "executable": false,
+                        "lamports": 1,
+                        "owner": program.to_string(),
+                        "rentEpoch": 0,
+                        "space": data.len()
+                    }
+                }]
+            }),
+        );
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let (slot, accounts) = tools::get_program_accounts_with_slot(
+            &rpc_client,
+            &program.to_string(),
+            &calculate_discriminator("Counter"),
+            &[],
+        )
+        .expect("Failed to fetch accounts");
+        assert_eq!(slot, 321);
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].0, account);
+        assert_eq!(accounts[0].1.data, data);
+    }
+
+    #[test]
+    fn test_decode_v0_message_with_lookup_tables() {
+        let payer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let table_key = Pubkey::new_unique();
+        let table = AddressLookupTableAccount {
+            key: table_key,
+            addresses: vec![Pubkey::new_unique(), recipient],
+        };
+        let ix = solana_sdk::system_instruction::transfer(&payer, &recipient, 10);
+        let message = v0::Message::try_compile(&payer, &[ix], std::slice::from_ref(&table), Hash::default()).unwrap();
+        let message_data = BASE64_STANDARD.encode(bincode::serialize(&VersionedMessage::V0(message)).unwrap());
+
+        let unresolved = tools::decode_message(&message_data).expect("Failed to decode message");
+        assert_eq!(unresolved.version, "0");
+        assert!(!unresolved.address_table_lookups[0].resolved);
+        assert_eq!(unresolved.instructions[0].accounts[1].pubkey, format!("{}[1]", table_key));
+
+        let lookup_tables = tools::parse_address_lookup_tables(
+            &json!({ table_key.to_string(): table.addresses.iter().map(|a| a.to_string()).collect::<Vec<_>>() })
+                .to_string(),
+        )
+        .unwrap();
+        let resolved = tools::decode_message_with_lookup_tables(&message_data, &lookup_tables)
+            .expect("Failed to decode message");
+        assert!(resolved.address_table_lookups[0].resolved);
+        let loaded = &resolved.instructions[0].accounts[1];
+        assert_eq!(loaded.pubkey, recipient.to_string());
+        assert!(loaded.writable && !loaded.signer);
+    }
+
+    #[test]
+    fn test_decode_idl_instructions() {
+        let idl = r#"{
+            "instructions": [{
+                "name": "depositFunds",
+                "accounts": [
+                    {"name": "owner", "isMut": true, "isSigner": true},
+                    {"name": "vault", "isMut": true, "isSigner": false},
+                    {"name": "programs", "accounts": [{"name": "systemProgram", "isMut": false, "isSigner": false}]}
+                ],
+                "args": [
+                    {"name": "amount", "type": "u64"},
+                    {"name": "config", "type": {"defined": "Config"}}
+                ]
+            }],
+            "types": [{"name": "Config", "type": {"kind": "struct", "fields": [
+                {"name": "fee", "type": "u16"},
+                {"name": "enabled", "type": "bool"}
+            ]}}]
+        }"#;
+        let program = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+
+        let discriminator = tools::calculate_instruction_discriminator("depositFunds");
+        assert_eq!(discriminator[..], Sha256::digest(b"global:deposit_funds")[..8]);
+
+        let mut data = discriminator.to_vec();
+        data.extend_from_slice(&500u64.to_le_bytes());
+        data.extend_from_slice(&25u16.to_le_bytes());
+        data.push(1);
+        let ix = Instruction::new_with_bytes(
+            program,
+            &data,
+            vec![
+                AccountMeta::new(owner, true),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(solana_sdk::system_program::id(), false),
+            ],
+        );
+        let transfer = solana_sdk::system_instruction::transfer(&owner, &vault, 1);
+        let message = Message::new(&[transfer, ix], Some(&owner));
+        let decoded = tools::decode_message(&BASE64_STANDARD.encode(message.serialize())).unwrap();
+
+        let instructions = tools::decode_idl_instructions(idl, &program.to_string(), &decoded)
+            .expect("Failed to decode instructions");
+        assert_eq!(instructions.len(), 1);
+        let (index, instruction) = &instructions[0];
+        let instruction = instruction.as_ref().expect("Instruction was not identified");
+        assert_eq!(*index, 1);
+        assert_eq!(instruction.name, "depositFunds");
+        assert_eq!(instruction.args["amount"], 500);
+        assert_eq!(instruction.args["config"]["fee"], 25);
+
+        let names: Vec<&str> = instruction.accounts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["owner", "vault", "programs.systemProgram"]);
+        assert_eq!(instruction.accounts[1].pubkey, vault.to_string());
+        assert!(instruction.accounts[0].signer);
+    }
+
+    #[test]
+    fn test_decode_events() {
+        let idl = r#"{"events": [{"name": "Deposited", "fields": [
+            {"name": "owner", "type": "publicKey", "index": false},
+            {"name": "amount", "type": "u64", "index": false}
+        ]}]}"#;
+        let program = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut event_data = tools::calculate_event_discriminator("Deposited").to_vec();
+        event_data.extend_from_slice(&owner.to_bytes());
+        event_data.extend_from_slice(&75u64.to_le_bytes());
+
+        let logs: Vec<String> = vec![
+            format!("Program {} invoke [1]", program),
+            "Program log: Instruction: DepositFunds".to_string(),
+            "Program 11111111111111111111111111111111 invoke [2]".to_string(),
+            "Program 11111111111111111111111111111111 success".to_string(),
+            format!("Program data: {}", BASE64_STANDARD.encode(&event_data)),
+            format!("Program data: {}", BASE64_STANDARD.encode([9u8; 16])),
+            format!("Program data: {}", BASE64_STANDARD.encode(&event_data[..20])),
+            format!("Program {} success", program),
+        ];
+
+        // Unknown discriminators are skipped, while a truncated event fails on its own
+        let events = tools::decode_events(idl, &logs).expect("Failed to decode events");
+        assert_eq!(events.len(), 2);
+        let event = events[0].as_ref().expect("Failed to decode event");
+        assert_eq!(event.name, "Deposited");
+        assert_eq!(event.program_id, Some(program.to_string()));
+        assert_eq!(event.data["owner"], owner.to_string());
+        assert_eq!(event.data["amount"], 75);
+        assert!(events[1].as_ref().unwrap_err().contains("Deposited"));
+    }
+
+    #[test]
+    fn test_explain_error_code() {
+        let idl = r#"{"errors": [{"code": 6001, "name": "FeeTooHigh", "msg": "Fee exceeds the configured maximum"}]}"#;
+        let idl_error = tools::explain_error_code(Some(idl), tools::parse_error_code("0x1771").unwrap())
+            .unwrap()
+            .expect("IDL error not found");
+        assert_eq!(idl_error.name, "FeeTooHigh");
+        assert_eq!(idl_error.source, tools::ErrorSource::Idl);
+
+        let tx_error = r#"{"err": {"InstructionError": [1, {"Custom": 2003}]}}"#;
+        let anchor_error = tools::explain_error_code(None, tools::parse_error_code(tx_error).unwrap())
+            .unwrap()
+            .expect("Anchor error not found");
+        assert_eq!(anchor_error.name, "ConstraintRaw");
+        assert_eq!(anchor_error.source, tools::ErrorSource::Anchor);
+
+        for (code, name) in [
+            (2006, "ConstraintSeeds"),
+            (3012, "AccountNotInitialized"),
+            (4100, "DeclaredProgramIdMismatch"),
+            (4102, "InvalidNumericConversion"),
+        ] {
+            let explained = tools::explain_error_code(None, code).unwrap().expect("Anchor error not found");
+            assert_eq!(explained.name, name);
+        }
+        assert!(tools::explain_error_code(None, 4000).unwrap().is_none());
+
+        assert_eq!(tools::parse_error_code("custom program error: 0x7d0").unwrap(), 2000);
+        assert!(tools::explain_error_code(None, 6001).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_fetch_transaction() {
+        let recorded: Value =
+            serde_json::from_str(&fs::read_to_string("./test/vault_transaction.json").unwrap()).unwrap();
+        let mut mocks = Mocks::new();
+        mocks.insert(RpcRequest::GetTransaction, recorded.clone());
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let signature = "1GMkH3brNXiNNs1tiFZHu4yZSRrzJwxi5wB9bHFtMinfCXNnR1adh8Vo8NTheK4evneedH4qmvjeqcBBNAefgS";
+        let transaction = tools::fetch_transaction(&rpc_client, signature).expect("Failed to fetch transaction");
+        assert_eq!(transaction.slot, 250000000);
+        assert_eq!(transaction.compute_units_consumed, Some(12345));
+        assert!(transaction.error.is_none());
+        assert_eq!(transaction.logs.len(), 7);
+
+        // Loaded addresses resolve the lookup so the system program appears as the instruction's third account
+        let instruction = &transaction.message.instructions[0];
+        assert_eq!(instruction.accounts[2].pubkey, "11111111111111111111111111111111");
+        let program = instruction.program_id.clone();
+        let idl = r#"{"instructions": [{"name": "depositFunds", "accounts": [], "args": [
+            {"name": "amount", "type": "u64"},
+            {"name": "fee", "type": "u16"},
+            {"name": "enabled", "type": "bool"}
+        ]}], "types": []}"#;
+        let idl_instructions = tools::decode_idl_instructions(idl, &program, &transaction.message).unwrap();
+        assert_eq!(idl_instructions[0].1.as_ref().unwrap().name, "depositFunds");
+
+        let inner = &transaction.inner_instructions[0];
+        assert_eq!(inner.index, 0);
+        assert_eq!(inner.instructions[0].accounts[1].pubkey, "LbUiWL3xVV8hTFYBVdbTNrpDo41NKS6o3LHHuDzjfcY");
+        assert!(inner.instructions[0].accounts[1].writable);
+
+        let change = &transaction.token_balance_changes[0];
+        assert_eq!((change.pre_amount.as_str(), change.post_amount.as_str()), ("1", "1.5"));
+        assert_eq!(change.change, "+0.5");
+
+        // Amounts of a mint reporting more decimals than can be scaled are shown in raw units
+        let mut oversized = recorded;
+        for balances in ["preTokenBalances", "postTokenBalances"] {
+            oversized["meta"][balances][0]["uiTokenAmount"]["decimals"] = json!(40);
+        }
+        let mut mocks = Mocks::new();
+        mocks.insert(RpcRequest::GetTransaction, oversized);
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+        let transaction = tools::fetch_transaction(&rpc_client, signature).expect("Failed to fetch transaction");
+        let change = &transaction.token_balance_changes[0];
+        assert_eq!((change.pre_amount.as_str(), change.post_amount.as_str()), ("1000000", "1500000"));
+        assert_eq!(change.change, "+500000");
+    }
+
+    // Local HTTP JSON-RPC stand-in that answers every request with the handler's result
+    fn serve_json_rpc(handler: impl Fn(&str, &Value) -> Value + Send + Sync + 'static) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let handler = Arc::new(handler);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let handler = handler.clone();
+                thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.unwrap());
+                    loop {
+                        let mut content_length = 0;
+                        loop {
+                            let mut line = String::new();
+                            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                                return;
+                            }
+                            if line == "\r\n" {
+                                break;
+                            }
+                            if let Some((name, value)) = line.split_once(':') {
+                                if name.eq_ignore_ascii_case("content-length") {
+                                    content_length = value.trim().parse().unwrap();
+                                }
+                            }
+                        }
+                        let mut body = vec![0; content_length];
+                        reader.read_exact(&mut body).unwrap();
+                        let request: Value = serde_json::from_slice(&body).unwrap();
+                        let result = handler(request["method"].as_str().unwrap(), &request["params"]);
+                        let response = json!({"jsonrpc": "2.0", "id": request["id"], "result": result}).to_string();
+                        write!(
+                            reader.get_mut(),
+                            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                            response.len(),
+                            response
+                        )
+                        .unwrap();
+                    }
+                });
+            }
+        });
+        url
+    }
+
+    #[test]
+    fn test_find_accounts_by_criteria_declared_discriminator() {
+        let idl = r#"{
+            "accounts": [{"name": "Pool", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]}],
+            "types": [{"name": "Pool", "type": {"kind": "struct", "fields": [
+                {"name": "liquidity", "type": "u64"}
+            ]}}]
+        }"#;
+        let url = serve_json_rpc(|method, params| {
+            assert_eq!(method, "getProgramAccounts");
+            let filters = params[1]["filters"].as_array().unwrap();
+            assert_eq!(filters[0]["memcmp"]["offset"], 0);
+            assert_eq!(filters[0]["memcmp"]["bytes"], BASE64_STANDARD.encode([1, 2, 3, 4, 5, 6, 7, 8]));
+            assert_eq!(filters[1]["memcmp"]["offset"], 8);
+            json!([])
+        });
+        let rpc_client = RpcClient::new(url);
+
+        let accounts = tools::find_accounts_by_criteria(
+            &rpc_client,
+            idl,
+            &Pubkey::new_unique().to_string(),
+            "Pool",
+            "liquidity",
+            &1000u64.to_le_bytes(),
+        )
+        .expect("Failed to find accounts");
+        assert!(accounts.is_empty());
+    }
+
+    #[test]
+    fn test_get_address_history() {
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let signature = |n: u8| solana_sdk::signature::Signature::from([n; 64]).to_string();
+        let history: HashMap<String, Vec<(String, u64)>> = HashMap::from([
+            (first.to_string(), vec![(signature(1), 30), (signature(2), 20), (signature(3), 10)]),
+            (second.to_string(), vec![(signature(4), 25), (signature(2), 20)]),
+        ]);
+
+        // Serve one signature per page so pagination has to follow `before`
+        let url = serve_json_rpc(move |method, params| {
+            assert_eq!(method, "getSignaturesForAddress");
+            let signatures = &history[params[0].as_str().unwrap()];
+            let start = params[1]["before"]
+                .as_str()
+                .map_or(0, |before| signatures.iter().position(|(s, _)| s == before).unwrap() + 1);
+            let page: Vec<Value> = signatures
+                .iter()
+                .skip(start)
+                .take(1)
+                .map(|(s, slot)| json!({"signature": s, "slot": slot, "err": null, "memo": null, "blockTime": null}))
+                .collect();
+            Value::Array(page)
+        });
+        let rpc_client = RpcClient::new(url);
+
+        let entries = tools::get_address_history(&rpc_client, &[first, second], 3, None, None, 2)
+            .expect("Failed to fetch history");
+        let slots: Vec<u64> = entries.iter().map(|entry| entry.slot).collect();
+        assert_eq!(slots, [30, 25, 20, 10]);
+        assert_eq!(entries[2].addresses, [first.to_string(), second.to_string()]);
+
+        let limited = tools::get_address_history(&rpc_client, &[first], 2, None, None, 1).unwrap();
+        assert_eq!(limited.len(), 2);
+    }
+
+    #[test]
+    fn test_build_instruction_from_idl() {
+        let idl = r#"{
+            "instructions": [{
+                "name": "depositFunds",
+                "accounts": [
+                    {"name": "owner", "isMut": true, "isSigner": true},
+                    {"name": "vault", "isMut": true, "isSigner": false},
+                    {"name": "programs", "accounts": [{"name": "systemProgram", "isMut": false, "isSigner": false}]}
+                ],
+                "args": [
+                    {"name": "amount", "type": "u64"},
+                    {"name": "config", "type": {"defined": "Config"}}
+                ]
+            }],
+            "types": [{"name": "Config", "type": {"kind": "struct", "fields": [
+                {"name": "fee", "type": "u16"},
+                {"name": "enabled", "type": "bool"}
+            ]}}]
+        }"#;
+        let program = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let accounts = HashMap::from([
+            ("owner".to_string(), owner),
+            ("vault".to_string(), vault),
+            ("programs.systemProgram".to_string(), Pubkey::default()),
+        ]);
+        let args = json!({"amount": "18446744073709551615", "config": {"fee": 30, "enabled": true}});
+
+        let instruction =
+            tools::build_instruction_from_idl(idl, &program.to_string(), "deposit_funds", &args, &accounts)
+                .expect("Failed to build instruction");
+        assert_eq!(instruction.accounts[0], AccountMeta::new(owner, true));
+        assert_eq!(instruction.accounts[2], AccountMeta::new_readonly(Pubkey::default(), false));
+
+        // Encoding then decoding the message gives back the same args and account names
+        let message = tools::encode_message(&[instruction], &owner, &Hash::default()).unwrap();
+        let decoded = tools::decode_message(&message).unwrap();
+        let results = tools::decode_idl_instructions(idl, &program.to_string(), &decoded).unwrap();
+        let decoded_instruction = results[0].1.as_ref().unwrap();
+        assert_eq!(decoded_instruction.args["amount"], u64::MAX);
+        assert_eq!(decoded_instruction.args["config"], args["config"]);
+        assert_eq!(decoded_instruction.accounts[1].pubkey, vault.to_string());
+
+        let missing = HashMap::from([("owner".to_string(), owner)]);
+        let error = tools::build_instruction_from_idl(idl, &program.to_string(), "depositFunds", &args, &missing)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "Missing account: vault");
+    }
+
+    #[test]
+    fn test_build_instruction_primitive_args() {
+        let idl = r#"{
+            "instructions": [{"name": "note", "accounts": [], "args": [
+                {"name": "text", "type": "string"},
+                {"name": "delta", "type": "i16"}
+            ]}],
+            "types": []
+        }"#;
+        let args = json!({"text": "abc", "delta": -2});
+        let instruction =
+            tools::build_instruction_from_idl(idl, &Pubkey::new_unique().to_string(), "note", &args, &HashMap::new())
+                .expect("Failed to build instruction");
+        assert_eq!(instruction.data[8..], [3, 0, 0, 0, b'a', b'b', b'c', 0xfe, 0xff]);
+    }
+
+    #[test]
+    fn test_encode_value_by_type() {
+        let owner = Pubkey::new_unique();
+        assert_eq!(tools::encode_value_by_type(&owner.to_string(), "pubkey").unwrap(), owner.to_bytes());
+        assert_eq!(tools::encode_value_by_type(&owner.to_string(), "publicKey").unwrap(), owner.to_bytes());
+        assert_eq!(tools::encode_value_by_type("513", "u16").unwrap(), [1, 2]);
+        assert_eq!(tools::encode_value_by_type("-1", "i32").unwrap(), [0xff; 4]);
+        assert_eq!(tools::encode_value_by_type("ab", "string").unwrap(), [2, 0, 0, 0, b'a', b'b']);
+        assert!(tools::encode_value_by_type("70000", "u16").is_err());
+    }
+
+    #[test]
+    fn test_simulate_message() {
+        let program = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let vault = Pubkey::new_unique();
+        let idl = r#"{
+            "accounts": [{"name": "Counter", "type": {"kind": "struct", "fields": [{"name": "count", "type": "u64"}]}}],
+            "types": [],
+            "errors": [{"code": 6001, "name": "FeeTooHigh", "msg": "Fee exceeds the configured maximum"}]
+        }"#;
+        let mut vault_data = tools::calculate_discriminator("Counter").to_vec();
+        vault_data.extend_from_slice(&900u64.to_le_bytes());
+
+        let mut mocks = Mocks::new();
+        mocks.insert(
+            RpcRequest::SimulateTransaction,
+            json!({
+                "context": {"slot": 42},
+                "value": {
+                    "err": {"InstructionError": [0, {"Custom": 6001}]},
+                    "logs": [
+                        format!("Program {} invoke [1]", program),
+                        format!("Program {} failed: custom program error: 0x1771", program)
+                    ],
+                    "accounts": [{
+                        "data": [BASE64_STANDARD.encode(&vault_data), "base64"],
+                        "executable": false,
+                        "lamports": 10,
+                        "owner": program.to_string(),
+                        "rentEpoch": 0,
+                        "space": vault_data.len()
+                    }],
+                    "unitsConsumed": 3100
+                }
+            }),
+        );
+        let rpc_client = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        let instruction = Instruction::new_with_bytes(program, &[1, 2, 3], vec![AccountMeta::new(owner, true)]);
+        let message = tools::encode_message(&[instruction], &owner, &Hash::default()).unwrap();
+        let simulated = tools::simulate_message(&rpc_client, &message, &[vault]).expect("Failed to simulate");
+
+        assert_eq!(simulated.slot, 42);
+        assert_eq!(simulated.units_consumed, Some(3100));
+        let code = tools::parse_error_code(&simulated.error.unwrap().to_string()).unwrap();
+        assert_eq!(tools::explain_error_code(Some(idl), code).unwrap().unwrap().name, "FeeTooHigh");
+
+        let (address, account) = &simulated.accounts[0];
+        assert_eq!(*address, vault);
+        let decoded = tools::decode_account_data(idl, "Counter", &account.as_ref().unwrap().data).unwrap();
+        assert_eq!(decoded["count"], 900);
+    }
+
+    #[test]
+    fn test_derive_pda_from_idl() {
+        let idl = r#"{
+            "instructions": [{
+                "name": "depositFunds",
+                "accounts": [
+                    {"name": "owner", "isMut": true, "isSigner": true},
+                    {"name": "vault", "isMut": true, "isSigner": false, "pda": {"seeds": [
+                        {"kind": "const", "value": [118, 97, 117, 108, 116]},
+                        {"kind": "account", "path": "owner"},
+                        {"kind": "arg", "path": "config.fee"}
+                    ]}}
+                ],
+                "args": [{"name": "config", "type": {"defined": "Config"}}]
+            }],
+            "types": [{"name": "Config", "type": {"kind": "struct", "fields": [
+                {"name": "fee", "type": "u16"},
+                {"name": "enabled", "type": "bool"}
+            ]}}]
+        }"#;
+        let program = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let seeds = HashMap::from([
+            ("owner".to_string(), owner.to_string()),
+            ("config.fee".to_string(), "25".to_string()),
+        ]);
+
+        let derived = tools::derive_pda_from_idl(idl, &program.to_string(), "Vault", &seeds, None)
+            .expect("Failed to derive PDA");
+        let expected = Pubkey::find_program_address(&[b"vault", owner.as_ref(), &25u16.to_le_bytes()], &program);
+        assert_eq!(derived, expected);
+
+        let missing = HashMap::from([("owner".to_string(), owner.to_string())]);
+        let error = tools::derive_pda_from_idl(idl, &program.to_string(), "vault", &missing, None).unwrap_err();
+        assert_eq!(error.to_string(), "Missing seed value: config.fee");
+    }
+
+    #[test]
+    fn test_get_multiple_accounts_batched() {
+        let program = Pubkey::new_unique();
+        let addresses: Vec<Pubkey> = (0..150).map(|_| Pubkey::new_unique()).collect();
+        let idl = r#"{"accounts": [{"name": "Counter", "type": {"kind": "struct", "fields": [
+            {"name": "count", "type": "u64"}
+        ]}}], "types": []}"#;
+        let mut counter_data = tools::calculate_discriminator("Counter").to_vec();
+        counter_data.extend_from_slice(&5u64.to_le_bytes());
+        let requested = Arc::new(Mutex::new(Vec::new()));
+
+        // Only the first address exists, every batch records how many keys it asked for
+        let (first, batches) = (addresses[0].to_string(), requested.clone());
+        let url = serve_json_rpc(move |method, params| {
+            assert_eq!(method, "getMultipleAccounts");
+            let keys = params[0].as_array().unwrap();
+            batches.lock().unwrap().push(keys.len());
+            let value: Vec<Value> = keys
+                .iter()
+                .map(|key| match key.as_str() == Some(first.as_str()) {
+                    true => json!({
+                        "data": [BASE64_STANDARD.encode(&counter_data), "base64"],
+                        "executable": false,
+                        "lamports": 10,
+                        "owner": program.to_string(),
+                        "rentEpoch": 0,
+                        "space": counter_data.len()
+                    }),
+                    false => Value::Null,
+                })
+                .collect();
+            json!({"context": {"slot": 1}, "value": value})
+        });
+        let rpc_client = RpcClient::new(url);
+
+        let accounts = tools::get_multiple_accounts_batched(&rpc_client, &addresses).expect("Failed to fetch accounts");
+        assert_eq!(*requested.lock().unwrap(), [100, 50]);
+        assert_eq!(accounts.len(), 150);
+        assert!(accounts[1..].iter().all(Option::is_none));
+
+        let data = &accounts[0].as_ref().unwrap().data;
+        assert_eq!(tools::detect_account_type(idl, data).unwrap().as_deref(), Some("Counter"));
+        assert_eq!(tools::detect_account_type(idl, &[0; 16]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_identify_account_data() {
+        let idl = r#"{"name": "vault", "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [
+            {"name": "amount", "type": "u64"},
+            {"name": "label", "type": "string"}
+        ]}}], "types": []}"#;
+        let mut data = tools::calculate_discriminator("Vault").to_vec();
+        data.extend_from_slice(&5u64.to_le_bytes());
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(b"abc");
+
+        let matched = tools::identify_account_data(idl, &data)
+            .expect("Failed to identify account")
+            .expect("No account type matched");
+        assert_eq!(matched.account, "Vault");
+        assert_eq!(matched.program_name.as_deref(), Some("vault"));
+        assert_eq!(matched.layout_size, None);
+        assert_eq!(matched.decoded_len, Some(data.len()));
+        assert!(matched.is_size_consistent());
+
+        let truncated = tools::identify_account_data(idl, &data[..20]).unwrap().unwrap();
+        assert_eq!(truncated.decoded_len, None);
+        assert!(!truncated.is_size_consistent());
+
+        assert!(tools::identify_account_data(idl, &[0; 64]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_identify_coption_account() {
+        let idl = r#"{"accounts": [{"name": "Mint", "type": {"kind": "struct", "fields": [
+            {"name": "authority", "type": {"coption": "publicKey"}},
+            {"name": "supply", "type": "u64"}
+        ]}}], "types": []}"#;
+        let mut data = tools::calculate_discriminator("Mint").to_vec();
+        data.extend_from_slice(&[0; 36]);
+        data.extend_from_slice(&1000u64.to_le_bytes());
+
+        // The COption tag counts towards the layout size, as it does when decoding
+        let matched = tools::identify_account_data(idl, &data).unwrap().expect("No account type matched");
+        assert_eq!(matched.layout_size, Some(52));
+        assert!(matched.is_size_consistent());
+        assert_eq!(tools::decode_account_data(idl, "Mint", &data).unwrap()["supply"], 1000);
+    }
+
+    #[test]
+    fn test_program_census() {
+        let idl = r#"{"accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [
+            {"name": "amount", "type": "u64"}
+        ]}}], "types": []}"#;
+        let program = Pubkey::new_unique();
+        let vault_discriminator = BASE64_STANDARD.encode(calculate_discriminator("Vault"));
+        let vaults = [(Pubkey::new_unique(), 10), (Pubkey::new_unique(), 20)];
+        let leftover = Pubkey::new_unique();
+
+        let url = serve_json_rpc(move |method, params| {
+            assert_eq!(method, "getProgramAccounts");
+            let config = &params[1];
+            let keyed = |pubkey: &Pubkey, lamports: u64, data: &[u8]| {
+                json!({"pubkey": pubkey.to_string(), "account": {
+                    "data": [BASE64_STANDARD.encode(data), "base64"],
+                    "executable": false,
+                    "lamports": lamports,
+                    "owner": program.to_string(),
+                    "rentEpoch": 0,
+                    "space": 0
+                }})
+            };
+            match config["filters"][0]["memcmp"]["bytes"].as_str() {
+                Some(bytes) => {
+                    assert_eq!(config["dataSlice"]["length"], 0);
+                    let matched = if bytes == vault_discriminator { &vaults[..] } else { &[] };
+                    matched.iter().map(|(pubkey, lamports)| keyed(pubkey, *lamports, &[])).collect()
+                }
+                None => {
+                    let mut all: Vec<Value> = vaults
+                        .iter()
+                        .map(|(pubkey, lamports)| keyed(pubkey, *lamports, &calculate_discriminator("Vault")))
+                        .collect();
+                    all.push(keyed(&leftover, 5, &[9; 8]));
+                    Value::Array(all)
+                }
+            }
+        });
+        let rpc_client = RpcClient::new(url);
+
+        let census = tools::program_census(&rpc_client, idl, &program.to_string()).expect("Failed to run census");
+        assert_eq!(census.types.len(), 1);
+        assert_eq!(census.types[0].account, "Vault");
+        assert_eq!((census.types[0].count, census.types[0].lamports), (2, 30));
+        assert_eq!(census.unknown.len(), 1);
+        assert_eq!(census.unknown[0].pubkey, leftover.to_string());
+        assert_eq!(census.unknown[0].discriminator, "0909090909090909");
+    }
+
+    #[test]
+    fn test_get_account_layout() {
+        let idl = r#"{
+            "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [
+                {"name": "owner", "type": "publicKey"},
+                {"name": "amount", "type": "u64"},
+                {"name": "config", "type": {"defined": "Config"}},
+                {"name": "label", "type": "string"},
+                {"name": "side", "type": {"defined": "Side"}},
+                {"name": "limit", "type": {"option": "u16"}}
+            ]}}],
+            "types": [
+                {"name": "Config", "type": {"kind": "struct", "fields": [
+                    {"name": "fee", "type": "u16"},
+                    {"name": "enabled", "type": "bool"}
+                ]}},
+                {"name": "Side", "type": {"kind": "enum", "variants": [{"name": "Long"}, {"name": "Short"}]}}
+            ]
+        }"#;
+        let layout = tools::get_account_layout(idl, "Vault").expect("Failed to get layout");
+        let rows: Vec<(&str, &str, Option<usize>, Option<usize>)> = layout
+            .iter()
+            .map(|field| (field.path.as_str(), field.field_type.as_str(), field.offset, field.size))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("owner", "publicKey", Some(8), Some(32)),
+                ("amount", "u64", Some(40), Some(8)),
+                ("config", "Config", Some(48), Some(3)),
+                ("config.fee", "u16", Some(48), Some(2)),
+                ("config.enabled", "bool", Some(50), Some(1)),
+                ("label", "string", Some(51), None),
+                ("side", "Side", None, Some(1)),
+                ("limit", "Option<u16>", None, None),
+            ]
+        );
+        assert_eq!(tools::account_layout_size(&layout), None);
+        assert_eq!(tools::get_variable_offset_from_idl(idl, "Vault", "config.enabled").unwrap(), 50);
+        assert!(tools::get_variable_offset_from_idl(idl, "Vault", "side").is_err());
+
+        // Types the IDL does not define are errors rather than dynamically sized fields
+        let broken = r#"{"accounts": [{"name": "Broken", "type": {"kind": "struct", "fields": [
+            {"name": "missing", "type": {"defined": "Missing"}}
+        ]}}], "types": []}"#;
+        let error = tools::get_account_layout(broken, "Broken").unwrap_err().to_string();
+        assert!(error.contains("Missing"), "{}", error);
+
+        // Only the fields before a variable need to resolve to find its offset
+        let trailing = r#"{"accounts": [{"name": "Trailing", "type": {"kind": "struct", "fields": [
+            {"name": "fee", "type": "u16"},
+            {"name": "amount", "type": "u64"},
+            {"name": "missing", "type": {"defined": "Missing"}}
+        ]}}], "types": []}"#;
+        assert_eq!(tools::get_variable_offset_from_idl(trailing, "Trailing", "amount").unwrap(), 10);
+        assert_eq!(tools::get_variable_offset_from_idl(trailing, "Trailing", "missing").unwrap(), 18);
+    }
+
+    #[test]
+    fn test_data_enum_layout() {
+        let idl = r#"{
+            "accounts": [{"name": "Order", "type": {"kind": "struct", "fields": [
+                {"name": "limit", "type": {"defined": "Limit"}},
+                {"name": "side", "type": {"defined": "Side"}}
+            ]}}],
+            "types": [
+                {"name": "Limit", "type": {"kind": "enum", "variants": [
+                    {"name": "None"},
+                    {"name": "Price", "fields": ["u64"]}
+                ]}},
+                {"name": "Side", "type": {"kind": "enum", "variants": [
+                    {"name": "Bid", "fields": ["u16"]},
+                    {"name": "Ask", "fields": [{"name": "ticks", "type": "u16"}]}
+                ]}}
+            ]
+        }"#;
+        let layout = tools::get_account_layout(idl, "Order").expect("Failed to get layout");
+        let rows: Vec<(&str, Option<usize>, Option<usize>)> =
+            layout.iter().map(|field| (field.path.as_str(), field.offset, field.size)).collect();
+        assert_eq!(rows, [("limit", Some(8), None), ("side", None, Some(3))]);
+
+        // An account holding the smaller variant is shorter than the largest one
+        let mut data = tools::calculate_discriminator("Order").to_vec();
+        data.extend_from_slice(&[0, 1, 4, 0]);
+        let validated = tools::validate_account_layout(idl, "Order", &data).unwrap();
+        assert_eq!((validated.mismatch, validated.checked_len), (None, Some(data.len())));
+        assert!(tools::identify_account_data(idl, &data).unwrap().unwrap().is_size_consistent());
+    }
+
+    #[test]
+    fn test_validate_account_layout() {
+        let idl = r#"{
+            "accounts": [{"name": "Position", "type": {"kind": "struct", "fields": [
+                {"name": "open", "type": "bool"},
+                {"name": "side", "type": {"defined": "Side"}},
+                {"name": "size", "type": "u64"}
+            ]}}],
+            "types": [{"name": "Side", "type": {"kind": "enum", "variants": [{"name": "Long"}, {"name": "Short"}]}}]
+        }"#;
+        let mut data = tools::calculate_discriminator("Position").to_vec();
+        data.extend_from_slice(&[1, 1]);
+        data.extend_from_slice(&5u64.to_le_bytes());
+
+        let valid = tools::validate_account_layout(idl, "Position", &data).expect("Failed to validate layout");
+        assert_eq!(valid.mismatch, None);
+        assert_eq!(valid.checked_len, Some(data.len()));
+
+        // Zeroed spare room is accepted, anything else past the layout is reported
+        let mut padded = data.clone();
+        padded.extend_from_slice(&[0, 0, 0, 4]);
+        assert_eq!(tools::validate_account_layout(idl, "Position", &padded).unwrap().mismatch.unwrap().offset, data.len() + 3);
+
+        let mut bad_bool = data.clone();
+        bad_bool[8] = 2;
+        let mismatch = tools::validate_account_layout(idl, "Position", &bad_bool).unwrap().mismatch.unwrap();
+        assert_eq!(mismatch.to_string(), "offset 8 (open): bool is 2, expected 0 or 1");
+
+        let mut bad_enum = data.clone();
+        bad_enum[9] = 7;
+        let mismatch = tools::validate_account_layout(idl, "Position", &bad_enum).unwrap().mismatch.unwrap();
+        assert_eq!((mismatch.offset, mismatch.path.as_str()), (9, "side"));
+
+        let truncated = tools::validate_account_layout(idl, "Position", &data[..12]).unwrap();
+        assert_eq!(truncated.mismatch.unwrap().path, "size");
+    }
+
+    #[test]
+    fn test_validate_vec_length() {
+        let idl = r#"{
+            "accounts": [{"name": "Queue", "type": {"kind": "struct", "fields": [
+                {"name": "items", "type": {"vec": "u8"}}
+            ]}}],
+            "types": []
+        }"#;
+        let mut data = tools::calculate_discriminator("Queue").to_vec();
+        data.extend_from_slice(&u32::MAX.to_le_bytes());
+        data.extend_from_slice(&[1, 2, 3]);
+
+        let mismatch = tools::validate_account_layout(idl, "Queue", &data).unwrap().mismatch.unwrap();
+        assert_eq!((mismatch.offset, mismatch.path.as_str()), (8, "items"));
+        assert_eq!(mismatch.reason, format!("vec length {} exceeds the 3 remaining bytes", u32::MAX));
+        let error = tools::decode_account_data(idl, "Queue", &data).unwrap_err().to_string();
+        assert!(error.contains("exceeds the 3 remaining bytes"), "{}", error);
+
+        // The bound uses the element size, so one u64 does not fit in three bytes either
+        let wide = idl.replace(r#""vec": "u8""#, r#""vec": "u64""#);
+        data[8..12].copy_from_slice(&1u32.to_le_bytes());
+        let mismatch = tools::validate_account_layout(&wide, "Queue", &data).unwrap().mismatch.unwrap();
+        assert_eq!(mismatch.reason, "vec length 1 exceeds the 3 remaining bytes");
+        assert!(tools::decode_account_data(&wide, "Queue", &data).unwrap_err().to_string().contains("exceeds"));
+    }
+
+    #[test]
+    fn test_decode_vec_of_none() {
+        let idl = r#"{
+            "accounts": [{"name": "Slots", "type": {"kind": "struct", "fields": [
+                {"name": "prices", "type": {"vec": {"option": "u64"}}}
+            ]}}],
+            "types": []
+        }"#;
+        // Three empty options take one byte each, well under the size of a u64
+        let mut data = tools::calculate_discriminator("Slots").to_vec();
+        data.extend_from_slice(&3u32.to_le_bytes());
+        data.extend_from_slice(&[0, 0, 0]);
+
+        assert_eq!(
+            tools::decode_account_data(idl, "Slots", &data).unwrap(),
+            serde_json::json!({"prices": [null, null, null]})
+        );
+        let validated = tools::validate_account_layout(idl, "Slots", &data).unwrap();
+        assert_eq!((validated.mismatch, validated.checked_len), (None, Some(data.len())));
+    }
+
+    #[test]
+    fn test_zero_copy_layout() {
+        let idl = r#"{
+            "accounts": [{"name": "Pool", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]}],
+            "types": [
+                {"name": "Pool", "serialization": "bytemuck", "repr": {"kind": "c"}, "type": {"kind": "struct", "fields": [
+                    {"name": "flag", "type": "u8"},
+                    {"name": "amount", "type": "u64"},
+                    {"name": "owner", "type": "pubkey"},
+                    {"name": "small", "type": "u16"},
+                    {"name": "inner", "type": {"defined": {"name": "Inner"}}},
+                    {"name": "last", "type": "u8"}
+                ]}},
+                {"name": "Inner", "type": {"kind": "struct", "fields": [
+                    {"name": "a", "type": "u8"},
+                    {"name": "b", "type": "u32"}
+                ]}}
+            ]
+        }"#;
+        let offsets = |layout: &[tools::FieldLayout]| -> Vec<(String, usize)> {
+            layout.iter().map(|field| (field.path.clone(), field.offset.unwrap())).collect()
+        };
+
+        assert_eq!(tools::detect_layout_mode(idl, "Pool").unwrap(), tools::LayoutMode::ReprC);
+        let layout = tools::get_account_layout(idl, "Pool").expect("Failed to get layout");
+        let expected = [("flag", 8), ("amount", 16), ("owner", 24), ("small", 56), ("inner", 60), ("inner.a", 60), ("inner.b", 64), ("last", 68)];
+        assert_eq!(offsets(&layout), expected.map(|(path, offset)| (path.to_string(), offset)));
+        assert_eq!(tools::account_layout_size(&layout), Some(72));
+        assert_eq!(tools::get_variable_offset_from_idl(idl, "Pool", "inner.b").unwrap(), 64);
+
+        let packed = tools::get_account_layout_with_mode(idl, "Pool", tools::LayoutMode::Packed).unwrap();
+        assert_eq!(packed.last().unwrap().offset, Some(56));
+        assert_eq!(tools::account_layout_size(&packed), Some(57));
+
+        // Padding bytes are not checked, but a bool or enum out of range would be
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.resize(72, 0);
+        data[9] = 0xff;
+        let validation = tools::validate_account_layout(idl, "Pool", &data).unwrap();
+        assert_eq!(validation.mismatch, None);
+        assert_eq!(validation.checked_len, Some(72));
+        assert!(tools::validate_account_layout(idl, "Pool", &data[..70]).unwrap().mismatch.is_some());
+
+        // A field without a fixed place is an error rather than a mismatch at the discriminator
+        let dynamic = idl.replace(r#"{"name": "small", "type": "u16"}"#, r#"{"name": "small", "type": {"vec": "u16"}}"#);
+        let error = tools::validate_account_layout(&dynamic, "Pool", &data).unwrap_err().to_string();
+        assert!(error.contains("small"), "{}", error);
+    }
+
+    #[test]
+    fn test_generic_types() {
+        let idl = r#"{
+            "accounts": [{"name": "Book", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]}],
+            "types": [
+                {"name": "Book", "type": {"kind": "struct", "fields": [
+                    {"name": "head", "type": "u8"},
+                    {"name": "orders", "type": {"defined": {"name": "Ring", "generics": [
+                        {"kind": "type", "type": {"defined": {"name": "Order"}}},
+                        {"kind": "const", "value": "2"}
+                    ]}}},
+                    {"name": "tail", "type": "u16"}
+                ]}},
+                {"name": "Ring", "generics": [{"kind": "type", "name": "T"}, {"kind": "const", "name": "N", "type": "usize"}],
+                 "type": {"kind": "struct", "fields": [
+                    {"name": "len", "type": "u8"},
+                    {"name": "items", "type": {"array": [{"generic": "T"}, {"generic": "N"}]}}
+                ]}},
+                {"name": "Order", "type": {"kind": "struct", "fields": [
+                    {"name": "price", "type": "u32"},
+                    {"name": "open", "type": "bool"}
+                ]}}
+            ]
+        }"#;
+
+        let layout = tools::get_account_layout(idl, "Book").expect("Failed to get layout");
+        let orders = layout.iter().find(|field| field.path == "orders").unwrap();
+        assert_eq!(orders.field_type, "Ring<Order, 2>");
+        assert_eq!(orders.size, Some(11));
+        assert_eq!(tools::get_variable_offset_from_idl(idl, "Book", "tail").unwrap(), 20);
+        assert_eq!(tools::account_layout_size(&layout), Some(22));
+
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 2];
+        data.extend_from_slice(&100u32.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&200u32.to_le_bytes());
+        data.push(0);
+        data.extend_from_slice(&7u16.to_le_bytes());
+        let decoded = tools::decode_account_data(idl, "Book", &data).expect("Failed to decode account");
+        assert_eq!(decoded["orders"]["items"][1], json!({"price": 200, "open": false}));
+        assert_eq!(decoded["tail"], 7);
+    }
+
+    #[test]
+    fn test_recursive_types() {
+        let idl = r#"{
+            "accounts": [
+                {"name": "List", "discriminator": [1, 2, 3, 4, 5, 6, 7, 8]},
+                {"name": "Pair", "discriminator": [8, 7, 6, 5, 4, 3, 2, 1]}
+            ],
+            "types": [
+                {"name": "List", "type": {"kind": "struct", "fields": [
+                    {"name": "head", "type": {"defined": {"name": "Node"}}},
+                    {"name": "count", "type": "u8"}
+                ]}},
+                {"name": "Node", "type": {"kind": "struct", "fields": [
+                    {"name": "value", "type": "u64"},
+                    {"name": "next", "type": {"option": {"defined": {"name": "Node"}}}}
+                ]}},
+                {"name": "Pair", "type": {"kind": "struct", "fields": [{"name": "left", "type": {"defined": {"name": "A"}}}]}},
+                {"name": "A", "type": {"kind": "struct", "fields": [{"name": "b", "type": {"defined": {"name": "B"}}}]}},
+                {"name": "B", "type": {"kind": "struct", "fields": [{"name": "a", "type": {"defined": {"name": "A"}}}]}}
+            ]
+        }"#;
+
+        // A boxed self-reference has no fixed size but the rest of the layout still resolves
+        let layout = tools::get_account_layout(idl, "List").expect("Failed to get layout");
+        let next = layout.iter().find(|field| field.path == "head.next").unwrap();
+        assert_eq!(next.size, None);
+        assert_eq!(layout.iter().find(|field| field.path == "head.value").unwrap().offset, Some(8));
+        let err = tools::get_variable_offset_from_idl(idl, "List", "count").unwrap_err();
+        assert_eq!(err.to_string(), "Field 'count' follows a dynamically sized field");
+
+        // Types that contain each other directly are rejected with the chain that loops
+        let err = tools::get_account_layout(idl, "Pair").unwrap_err();
+        assert_eq!(err.to_string(), "Recursive type: A -> B -> A");
+        assert!(tools::get_account_field_paths(idl, "Pair").is_err());
+        let err = tools::decode_account_data(idl, "Pair", &[8, 7, 6, 5, 4, 3, 2, 1, 0]).unwrap_err();
+        assert_eq!(err.to_string(), "Recursive type: A -> B -> A");
+        let err = tools::validate_account_layout(idl, "Pair", &[8, 7, 6, 5, 4, 3, 2, 1, 0]).unwrap_err();
+        assert_eq!(err.to_string(), "Recursive type: A -> B -> A");
+
+        // A list behind an option still decodes, since each node starts further into the data
+        let mut data = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        data.extend_from_slice(&1u64.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(&2u64.to_le_bytes());
+        data.extend_from_slice(&[0, 3]);
+        let decoded = tools::decode_account_data(idl, "List", &data).expect("Failed to decode list");
+        assert_eq!(decoded["head"], json!({"value": 1, "next": {"value": 2, "next": null}}));
+        assert_eq!(decoded["count"], 3);
+
+        // A long but finite chain of nested types stops at the depth limit
+        let mut types: Vec<Value> = (0..100)
+            .map(|i| json!({"name": format!("T{}", i), "type": {"kind": "struct", "fields": [
+                {"name": "inner", "type": {"defined": {"name": format!("T{}", i + 1)}}}
+            ]}}))
+            .collect();
+        types.push(json!({"name": "T100", "type": {"kind": "struct", "fields": [{"name": "x", "type": "u8"}]}}));
+        types.push(json!({"name": "Deep", "type": {"kind": "struct", "fields": [
+            {"name": "root", "type": {"defined": {"name": "T0"}}}
+        ]}}));
+        let deep_idl = json!({"accounts": [{"name": "Deep", "discriminator": [0, 0, 0, 0, 0, 0, 0, 0]}], "types": types}).to_string();
+        let err = tools::get_account_layout(&deep_idl, "Deep").unwrap_err();
+        assert!(err.to_string().starts_with("Type nesting exceeds 64 levels: T0 -> T1"));
+    }
+
+    #[test]
+    fn test_diff_idls() {
+        let old_idl = r#"{
+            "accounts": [
+                {"name": "Pool", "type": {"kind": "struct", "fields": [
+                    {"name": "authority", "type": "publicKey"},
+                    {"name": "fee", "type": "u16"},
+                    {"name": "stats", "type": {"defined": "Stats"}},
+                    {"name": "bump", "type": "u8"}
+                ]}},
+                {"name": "Legacy", "type": {"kind": "struct", "fields": [{"name": "x", "type": "u8"}]}}
+            ],
+            "types": [{"name": "Stats", "type": {"kind": "struct", "fields": [
+                {"name": "volume", "type": "u64"},
+                {"name": "trades", "type": "u32"}
+            ]}}]
+        }"#;
+        let new_idl = r#"{
+            "accounts": [
+                {"name": "Pool", "type": {"kind": "struct", "fields": [
+                    {"name": "authority", "type": "publicKey"},
+                    {"name": "fee", "type": "u32"},
+                    {"name": "name", "type": "string"},
+                    {"name": "stats", "type": {"defined": "Stats"}},
+                    {"name": "bump", "type": "u8"}
+                ]}},
+                {"name": "Position", "type": {"kind": "struct", "fields": [{"name": "size", "type": "u64"}]}}
+            ],
+            "types": [{"name": "Stats", "type": {"kind": "struct", "fields": [
+                {"name": "trades", "type": "u32"},
+                {"name": "volume", "type": "u64"}
+            ]}}]
+        }"#;
+
+        let changes = tools::diff_idls(old_idl, new_idl).expect("Failed to diff IDLs");
+        let lines: Vec<String> = changes.iter().map(|change| change.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Pool.stats: fields-reordered volume, trades -> trades, volume",
+                "Pool.fee: type-changed u16 -> u32",
+                "Pool.stats: became-dynamic 42 -> dynamic",
+                "Pool.stats.volume: became-dynamic 42 -> dynamic",
+                "Pool.stats.trades: became-dynamic 50 -> dynamic",
+                "Pool.bump: became-dynamic 54 -> dynamic",
+                "Pool.name: field-added string",
+                "Legacy: account-removed",
+                "Position: account-added",
+            ]
+        );
+        assert!(changes.iter().filter(|change| change.kind != tools::IdlChangeKind::FieldAdded
+            && change.kind != tools::IdlChangeKind::AccountAdded).all(|change| change.breaking));
+        assert!(!changes.iter().find(|change| change.kind == tools::IdlChangeKind::AccountAdded).unwrap().breaking);
+
+        assert!(tools::diff_idls(old_idl, old_idl).unwrap().is_empty());
+
+        // Growing a layout breaks existing accounts, shrinking it only breaks the fields it dropped
+        let sized = |fields: &str| {
+            format!(r#"{{"accounts": [{{"name": "Pool", "type": {{"kind": "struct", "fields": [{}]}}}}], "types": []}}"#, fields)
+        };
+        let short = sized(r#"{"name": "fee", "type": "u16"}"#);
+        let long = sized(r#"{"name": "fee", "type": "u16"}, {"name": "padding", "type": {"array": ["u8", 6]}}"#);
+        let summary = |changes: Vec<tools::IdlChange>| -> Vec<(String, bool)> {
+            changes.iter().map(|change| (change.to_string(), change.breaking)).collect()
+        };
+        assert_eq!(
+            summary(tools::diff_idls(&short, &long).unwrap()),
+            [("Pool: size-changed 10 -> 16".to_string(), true), ("Pool.padding: field-added [u8; 6]".to_string(), false)]
+        );
+        assert_eq!(
+            summary(tools::diff_idls(&long, &short).unwrap()),
+            [("Pool: size-changed 16 -> 10".to_string(), false), ("Pool.padding: field-removed [u8; 6]".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn test_load_program_idl() {
+        use flate2::{write::ZlibEncoder, Compression};
+        use std::io::Write as _;
+
+        let program = Pubkey::new_unique();
+        let (base, _) = Pubkey::find_program_address(&[], &program);
+        let idl_address = Pubkey::create_with_seed(&base, "anchor:idl", &program).unwrap();
+        assert_eq!(tools::idl_account_address(&program).unwrap(), idl_address);
+
+        let stored = r#"{"name": "vault", "instructions": []}"#;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(stored.as_bytes()).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut data = calculate_discriminator("IdlAccount").to_vec();
+        data.extend_from_slice(Pubkey::new_unique().as_ref());
+        data.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+        data.extend_from_slice(&compressed);
+        // IDL accounts are allocated with room to grow, so the compressed bytes are followed by zeros
+        data.extend_from_slice(&[0; 64]);
+
+        let fetches = Arc::new(Mutex::new(0));
+        let counter = fetches.clone();
+        let url = serve_json_rpc(move |method, params| {
+            assert_eq!(method, "getAccountInfo");
+            assert_eq!(params[0], idl_address.to_string());
+            *counter.lock().unwrap() += 1;
+            json!({"context": {"slot": 1}, "value": {
+                "data": [BASE64_STANDARD.encode(&data), "base64"],
+                "executable": false,
+                "lamports": 10,
+                "owner": program.to_string(),
+                "rentEpoch": 0,
+                "space": data.len()
+            }})
+        });
+        let rpc_client = RpcClient::new(url);
+
+        let cache_dir = std::env::temp_dir().join(format!("sol-tools-idl-{}", program));
+        let idl = tools::load_program_idl(&rpc_client, &program, &cache_dir, false).expect("Failed to load IDL");
+        assert_eq!(idl, stored);
+        assert_eq!(std::fs::read_to_string(cache_dir.join(format!("{}.json", program))).unwrap(), stored);
+
+        // The cached copy is used until a refresh is asked for
+        tools::load_program_idl(&rpc_client, &program, &cache_dir, false).unwrap();
+        assert_eq!(*fetches.lock().unwrap(), 1);
+        tools::load_program_idl(&rpc_client, &program, &cache_dir, true).unwrap();
+        assert_eq!(*fetches.lock().unwrap(), 2);
+        std::fs::write(cache_dir.join(format!("{}.json", program)), &stored[..10]).unwrap();
+        assert_eq!(tools::load_program_idl(&rpc_client, &program, &cache_dir, false).unwrap(), stored);
+        assert_eq!(*fetches.lock().unwrap(), 3);
+        std::fs::remove_dir_all(&cache_dir).unwrap();
+
+        assert!(tools::decode_idl_account(&[0; 80]).is_err());
+    }
+
+    #[test]
+    fn test_idl_registry() {
+        let dir = std::env::temp_dir().join(format!("sol-tools-registry-{}", Pubkey::new_unique()));
+        std::fs::create_dir_all(dir.join("vendor")).unwrap();
+        let (embedded, mapped, overridden) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        // One IDL names its program in metadata.address, the other is only reachable through the mapping
+        let with_address = json!({"name": "vault", "metadata": {"address": embedded.to_string()}});
+        std::fs::write(dir.join("vault.json"), with_address.to_string()).unwrap();
+        let stale = json!({"name": "vault", "address": overridden.to_string()});
+        std::fs::write(dir.join("stale.json"), stale.to_string()).unwrap();
+        std::fs::write(dir.join("vendor/perpetuals.json"), r#"{"name": "perpetuals"}"#).unwrap();
+        std::fs::write(
+            dir.join("registry.toml"),
+            format!("[programs]\n{} = \"vendor/perpetuals.json\"\n{} = \"vault.json\"\n", mapped, overridden),
+        )
+        .unwrap();
+
+        let registry = tools::IdlRegistry::load(&dir).expect("Failed to load registry");
+        assert_eq!(registry.idl_path(&embedded.to_string()), Some(dir.join("vault.json").as_path()));
+        assert_eq!(registry.idl_path(&mapped.to_string()), Some(dir.join("vendor/perpetuals.json").as_path()));
+        assert_eq!(registry.idl_path(&overridden.to_string()), Some(dir.join("vault.json").as_path()));
+        assert_eq!(registry.programs().count(), 3);
+        assert_eq!(registry.load_idl(&mapped.to_string()).unwrap().as_deref(), Some(r#"{"name": "perpetuals"}"#));
+        assert_eq!(registry.load_idl(&Pubkey::new_unique().to_string()).unwrap(), None);
+
+        std::fs::write(dir.join("registry.toml"), "[programs]\nnot-a-program = \"vault.json\"\n").unwrap();
+        assert!(tools::IdlRegistry::load(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // A missing registry directory is an empty registry
+        assert_eq!(tools::IdlRegistry::load(&dir).unwrap().programs().count(), 0);
+    }
 }
@@ -0,0 +1,142 @@
+use clap::Args;
+use serde_json::Value;
+use sol_tools::tools::{build_instruction_from_idl, encode_message, get_program_address_from_idl};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::registry::resolve_idl;
+
+/// Arguments selecting an IDL instruction and its inputs
+#[derive(Args, Debug)]
+pub struct InstructionArgs {
+    /// Name of the IDL instruction to build
+    #[arg(long = "instruction", value_name = "INSTRUCTION_NAME")]
+    instruction: String,
+
+    /// Instruction args as a JSON object (inline or a file path)
+    #[arg(long = "args", value_name = "ARGS_JSON", default_value = "{}")]
+    args: String,
+
+    /// Account for the instruction as NAME=PUBKEY, nested accounts as GROUP.NAME=PUBKEY (can be specified multiple times)
+    #[arg(short, long = "account", value_name = "NAME=PUBKEY")]
+    accounts: Vec<String>,
+
+    /// Fee payer (defaults to the first signer account)
+    #[arg(long = "payer", value_name = "PUBKEY")]
+    payer: Option<String>,
+}
+
+/// Arguments for building an unsigned transaction message from an IDL instruction
+#[derive(Args, Debug)]
+pub struct BuildTxArgs {
+    /// Path to the IDL JSON file (defaults to the IDL registered for the program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH", required_unless_present = "program")]
+    idl: Option<String>,
+
+    #[command(flatten)]
+    instruction: InstructionArgs,
+
+    /// Program the IDL belongs to (defaults to the address in the IDL)
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// Recent blockhash to include in the message
+    #[arg(long = "blockhash", value_name = "BLOCKHASH", conflicts_with = "rpc")]
+    blockhash: Option<String>,
+
+    /// RPC URL used to fetch the latest blockhash
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: Option<String>,
+}
+
+// Build the instruction and print the unsigned message as base64
+pub fn run(args: &BuildTxArgs) {
+    let idl = resolve_idl(args.idl.as_deref(), args.program.as_deref());
+    let program = resolve_program(&idl, args.program.as_deref());
+
+    let blockhash = match (&args.blockhash, &args.rpc) {
+        (Some(blockhash), _) => Hash::from_str(blockhash).unwrap_or_else(|e| {
+            eprintln!("Error: Invalid blockhash {}: {}", blockhash, e);
+            std::process::exit(1);
+        }),
+        (None, Some(rpc)) => RpcClient::new(rpc.clone()).get_latest_blockhash().unwrap_or_else(|e| {
+            eprintln!("Error fetching latest blockhash: {}", e);
+            std::process::exit(1);
+        }),
+        (None, None) => {
+            eprintln!("Warning: No --blockhash or --rpc given, using the default blockhash");
+            Hash::default()
+        }
+    };
+
+    println!("{}", build_message(&args.instruction, &idl, &program, &blockhash));
+}
+
+// Use the given program or fall back to the address in the IDL
+pub fn resolve_program(idl: &str, program: Option<&str>) -> String {
+    match program {
+        Some(program) => program.to_string(),
+        None => get_program_address_from_idl(idl).unwrap_or_else(|e| {
+            eprintln!("Error: {}, use --program to specify it", e);
+            std::process::exit(1);
+        }),
+    }
+}
+
+// Build the selected instruction and encode it into an unsigned base64 message
+pub fn build_message(args: &InstructionArgs, idl: &str, program: &str, blockhash: &Hash) -> String {
+    let args_json = if Path::new(&args.args).is_file() {
+        std::fs::read_to_string(&args.args).expect("Failed to read args file")
+    } else {
+        args.args.clone()
+    };
+    let instruction_args: Value = serde_json::from_str(&args_json).unwrap_or_else(|e| {
+        eprintln!("Error parsing instruction args: {}", e);
+        std::process::exit(1);
+    });
+    let accounts: HashMap<String, Pubkey> = args.accounts.iter().map(|account| parse_account(account)).collect();
+
+    let instruction = build_instruction_from_idl(idl, program, &args.instruction, &instruction_args, &accounts)
+        .unwrap_or_else(|e| {
+            eprintln!("Error building instruction: {}", e);
+            std::process::exit(1);
+        });
+
+    let payer = match &args.payer {
+        Some(payer) => parse_pubkey(payer),
+        None => instruction
+            .accounts
+            .iter()
+            .find(|account| account.is_signer)
+            .map(|account| account.pubkey)
+            .unwrap_or_else(|| {
+                eprintln!("Error: The instruction has no signer, use --payer to specify the fee payer");
+                std::process::exit(1);
+            }),
+    };
+
+    encode_message(&[instruction], &payer, blockhash).unwrap_or_else(|e| {
+        eprintln!("Error encoding message: {}", e);
+        std::process::exit(1);
+    })
+}
+
+// Parse a NAME=PUBKEY account argument
+fn parse_account(account: &str) -> (String, Pubkey) {
+    let Some((name, pubkey)) = account.split_once('=') else {
+        eprintln!("Error: Account '{}' must be given as NAME=PUBKEY", account);
+        std::process::exit(1);
+    };
+    (name.to_string(), parse_pubkey(pubkey))
+}
+
+// Parse a pubkey argument, exiting on invalid input
+pub fn parse_pubkey(pubkey: &str) -> Pubkey {
+    Pubkey::from_str(pubkey).unwrap_or_else(|e| {
+        eprintln!("Error: Invalid pubkey {}: {}", pubkey, e);
+        std::process::exit(1);
+    })
+}
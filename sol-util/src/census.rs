@@ -0,0 +1,81 @@
+use clap::Args;
+use sol_tools::tools::program_census;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::native_token::lamports_to_sol;
+
+use crate::build_tx::resolve_program;
+use crate::registry::resolve_idl;
+
+/// Arguments for counting a program's accounts by type
+#[derive(Args, Debug)]
+pub struct CensusArgs {
+    /// Custom RPC URL
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: String,
+
+    /// Path to the IDL JSON file (defaults to the IDL registered for the program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH", required_unless_present = "program")]
+    idl: Option<String>,
+
+    /// Program ID of the Solana program (defaults to the address in the IDL)
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// Print the census as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Count every IDL account type the program owns and list the accounts matching none of them
+pub fn run(args: &CensusArgs) {
+    let idl = resolve_idl(args.idl.as_deref(), args.program.as_deref());
+    let program = resolve_program(&idl, args.program.as_deref());
+
+    let rpc_client = RpcClient::new(args.rpc.clone());
+    let census = program_census(&rpc_client, &idl, &program).unwrap_or_else(|e| {
+        eprintln!("Error taking census of {}: {}", program, e);
+        std::process::exit(1);
+    });
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&census).expect("Failed to format JSON"));
+        return;
+    }
+
+    let width = census.types.iter().map(|t| t.account.len()).max().unwrap_or(0).max("Unknown".len());
+    println!("{:<width$}  {:>10}  {:>20}", "Account type", "Count", "Rent (SOL)", width = width);
+    for account_type in &census.types {
+        println!(
+            "{:<width$}  {:>10}  {:>20}",
+            account_type.account,
+            account_type.count,
+            lamports_to_sol(account_type.lamports),
+            width = width
+        );
+    }
+    let unknown_lamports: u64 = census.unknown.iter().map(|account| account.lamports).sum();
+    println!(
+        "{:<width$}  {:>10}  {:>20}",
+        "Unknown",
+        census.unknown.len(),
+        lamports_to_sol(unknown_lamports),
+        width = width
+    );
+
+    let total_count: usize = census.types.iter().map(|t| t.count).sum::<usize>() + census.unknown.len();
+    let total_lamports: u64 = census.types.iter().map(|t| t.lamports).sum::<u64>() + unknown_lamports;
+    println!(
+        "{:<width$}  {:>10}  {:>20}",
+        "Total",
+        total_count,
+        lamports_to_sol(total_lamports),
+        width = width
+    );
+
+    if !census.unknown.is_empty() {
+        println!("\nAccounts matching no IDL account type:");
+        for account in &census.unknown {
+            println!("    {} (discriminator {}, {} lamports)", account.pubkey, account.discriminator, account.lamports);
+        }
+    }
+}
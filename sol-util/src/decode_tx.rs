@@ -0,0 +1,165 @@
+use clap::Args;
+use sol_tools::tools::{
+    decode_idl_instructions, decode_message, decode_message_with_lookup_tables, fetch_address_lookup_tables,
+    get_program_address_from_idl, parse_address_lookup_tables, DecodedMessage, IdlInstructionResults, LookupTables,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::registry::{add_registered_programs, resolve_idl};
+
+/// Arguments for decoding a transaction message
+#[derive(Args, Debug)]
+pub struct DecodeTxArgs {
+    /// Base64 encoded transaction message (legacy or v0)
+    #[arg(value_name = "MESSAGE")]
+    message: String,
+
+    /// Print the decoded message as JSON
+    #[arg(long = "json")]
+    json: bool,
+
+    /// RPC URL used to fetch address lookup tables referenced by a v0 message
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: Option<String>,
+
+    /// JSON file mapping lookup table addresses to their address lists
+    #[arg(long = "alt", value_name = "ALT_FILE")]
+    alt: Option<String>,
+
+    /// IDL used to decode instructions for its program (defaults to every registered IDL)
+    #[arg(short, long = "idl", value_name = "IDL_PATH")]
+    idl: Option<String>,
+
+    /// Program the IDL belongs to (defaults to the address in the IDL)
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+}
+
+// Decode a message and print it in the requested format
+pub fn run(args: &DecodeTxArgs) {
+    let mut decoded = decode_message(&args.message).unwrap_or_else(|e| {
+        eprintln!("Error decoding message: {}", e);
+        std::process::exit(1);
+    });
+
+    if !decoded.address_table_lookups.is_empty() {
+        let table_keys: Vec<String> = decoded
+            .address_table_lookups
+            .iter()
+            .map(|lookup| lookup.account_key.clone())
+            .collect();
+        let lookup_tables = load_lookup_tables(args, &table_keys);
+        if !lookup_tables.is_empty() {
+            decoded = decode_message_with_lookup_tables(&args.message, &lookup_tables).unwrap_or_else(|e| {
+                eprintln!("Error resolving lookup tables: {}", e);
+                std::process::exit(1);
+            });
+        }
+        if decoded.address_table_lookups.iter().any(|lookup| !lookup.resolved) {
+            eprintln!("Warning: some lookup tables were not resolved, use --rpc or --alt to load them");
+        }
+    }
+
+    let idl_instructions = match (&args.idl, &args.program) {
+        (None, None) => decode_with_registry(&decoded),
+        _ => Some(decode_with_idl(args.idl.as_deref(), args.program.as_deref(), &decoded)),
+    };
+
+    if args.json {
+        let mut json = serde_json::to_value(&decoded).expect("Failed to format JSON");
+        if let Some(idl_instructions) = &idl_instructions {
+            json["idl_instructions"] = idl_instructions
+                .iter()
+                .map(|(index, instruction)| match instruction {
+                    Ok(instruction) => serde_json::json!({ "index": index, "instruction": instruction }),
+                    Err(e) => serde_json::json!({ "index": index, "error": e }),
+                })
+                .collect();
+        }
+        println!("{}", serde_json::to_string_pretty(&json).expect("Failed to format JSON"));
+    } else {
+        print!("{}", decoded);
+        if let Some(idl_instructions) = &idl_instructions {
+            println!("IDL instructions:");
+            for (index, instruction) in idl_instructions {
+                match instruction {
+                    Ok(instruction) => println!("  [{}] {}", index, instruction.to_string().replace('\n', "\n      ")),
+                    Err(e) => println!("  [{}] Unrecognized: {}", index, e),
+                }
+            }
+        }
+    }
+}
+
+// Decode the message's instructions for the IDL's program
+fn decode_with_idl(
+    idl_path: Option<&str>,
+    program: Option<&str>,
+    decoded: &DecodedMessage,
+) -> IdlInstructionResults {
+    let idl = resolve_idl(idl_path, program);
+    let program = match program {
+        Some(program) => program.to_string(),
+        None => get_program_address_from_idl(&idl).unwrap_or_else(|e| {
+            eprintln!("Error: {}, use --program to specify it", e);
+            std::process::exit(1);
+        }),
+    };
+
+    decode_idl_instructions(&idl, &program, decoded).unwrap_or_else(|e| {
+        eprintln!("Error decoding instructions with IDL: {}", e);
+        std::process::exit(1);
+    })
+}
+
+// Decode the instructions of every registered program, or nothing when the registry is empty
+fn decode_with_registry(decoded: &DecodedMessage) -> Option<IdlInstructionResults> {
+    let mut programs = Vec::new();
+    add_registered_programs(&mut programs);
+    if programs.is_empty() {
+        return None;
+    }
+
+    let mut results = IdlInstructionResults::new();
+    for (program, idl) in &programs {
+        match decode_idl_instructions(idl, program, decoded) {
+            Ok(program_results) => results.extend(program_results),
+            Err(e) => eprintln!("Skipping the registered IDL for {}: {}", program, e),
+        }
+    }
+    results.sort_by_key(|(index, _)| *index);
+    Some(results)
+}
+
+// Load lookup tables from the local file first, then fetch any remaining ones over RPC
+fn load_lookup_tables(args: &DecodeTxArgs, table_keys: &[String]) -> LookupTables {
+    let mut lookup_tables = match &args.alt {
+        Some(path) => {
+            let contents = std::fs::read_to_string(path).expect("Failed to read lookup table file");
+            parse_address_lookup_tables(&contents).unwrap_or_else(|e| {
+                eprintln!("Error parsing lookup table file: {}", e);
+                std::process::exit(1);
+            })
+        }
+        None => LookupTables::new(),
+    };
+
+    if let Some(rpc) = &args.rpc {
+        let missing: Vec<Pubkey> = table_keys
+            .iter()
+            .filter_map(|key| Pubkey::from_str(key).ok())
+            .filter(|key| !lookup_tables.contains_key(key))
+            .collect();
+        if !missing.is_empty() {
+            let rpc_client = RpcClient::new(rpc.clone());
+            match fetch_address_lookup_tables(&rpc_client, &missing) {
+                Ok(fetched) => lookup_tables.extend(fetched),
+                Err(e) => eprintln!("Error fetching lookup tables: {}", e),
+            }
+        }
+    }
+
+    lookup_tables
+}
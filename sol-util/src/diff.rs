@@ -0,0 +1,92 @@
+use clap::Args;
+use sol_tools::tools::diff_account_data;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::collections::HashMap;
+
+use crate::load_accounts_from_file;
+use crate::registry::resolve_idl;
+
+/// Arguments for diffing two account dumps
+#[derive(Args, Debug)]
+pub struct DiffArgs {
+    /// Older dump written with --output
+    #[arg(value_name = "OLD_DUMP")]
+    old: String,
+
+    /// Newer dump written with --output
+    #[arg(value_name = "NEW_DUMP")]
+    new: String,
+
+    /// Path to the IDL JSON file (defaults to the IDL registered for --program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH", required_unless_present = "program")]
+    idl: Option<String>,
+
+    /// Program whose registered IDL is used when --idl is not given
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// Name of the account type stored in the dumps
+    #[arg(short = 'n', long = "name", value_name = "ACCOUNT_NAME")]
+    account: String,
+}
+
+// Report created, closed and changed accounts between two dumps
+pub fn run(args: &DiffArgs) {
+    let idl = resolve_idl(args.idl.as_deref(), args.program.as_deref());
+    let old_accounts = load_dump(&args.old);
+    let new_accounts = load_dump(&args.new);
+
+    let old_by_key: HashMap<Pubkey, &Account> = old_accounts.iter().map(|(k, a)| (*k, a)).collect();
+    let new_by_key: HashMap<Pubkey, &Account> = new_accounts.iter().map(|(k, a)| (*k, a)).collect();
+
+    let created: Vec<&Pubkey> = new_accounts
+        .iter()
+        .map(|(k, _)| k)
+        .filter(|k| !old_by_key.contains_key(k))
+        .collect();
+    let closed: Vec<&Pubkey> = old_accounts
+        .iter()
+        .map(|(k, _)| k)
+        .filter(|k| !new_by_key.contains_key(k))
+        .collect();
+
+    println!("Created accounts ({}):", created.len());
+    for pubkey in &created {
+        println!("  + {}", pubkey);
+    }
+
+    println!("Closed accounts ({}):", closed.len());
+    for pubkey in &closed {
+        println!("  - {}", pubkey);
+    }
+
+    let mut changed = Vec::new();
+    for (pubkey, old_account) in &old_accounts {
+        let Some(new_account) = new_by_key.get(pubkey) else {
+            continue;
+        };
+        if old_account.data == new_account.data {
+            continue;
+        }
+        match diff_account_data(&idl, &args.account, &old_account.data, &new_account.data) {
+            Ok(changes) => changed.push((pubkey, changes)),
+            Err(e) => eprintln!("Error decoding {}: {}", pubkey, e),
+        }
+    }
+
+    println!("Changed accounts ({}):", changed.len());
+    for (pubkey, changes) in changed {
+        println!("  {}", pubkey);
+        for change in changes {
+            println!("    {}", change);
+        }
+    }
+}
+
+// Load a dump or exit with an error
+fn load_dump(path: &str) -> Vec<(Pubkey, Account)> {
+    load_accounts_from_file(path).unwrap_or_else(|e| {
+        eprintln!("Error reading dump {}: {}", path, e);
+        std::process::exit(1);
+    })
+}
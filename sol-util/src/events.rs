@@ -0,0 +1,101 @@
+use clap::Args;
+use serde_json::Value;
+use sol_tools::tools::{decode_events, fetch_transaction_logs};
+use solana_client::rpc_client::RpcClient;
+
+use crate::registry::resolve_idl;
+
+/// Arguments for decoding Anchor events from transaction logs
+#[derive(Args, Debug)]
+pub struct EventsArgs {
+    /// Path to the IDL JSON file (defaults to the IDL registered for --program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH", required_unless_present = "program")]
+    idl: Option<String>,
+
+    /// Program whose registered IDL is used when --idl is not given
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// Saved transaction JSON (a getTransaction result) to read logs from
+    #[arg(short, long = "file", value_name = "TX_FILE", conflicts_with = "signature")]
+    file: Option<String>,
+
+    /// Signature of a confirmed transaction to fetch logs for
+    #[arg(short, long = "signature", value_name = "SIGNATURE", requires = "rpc")]
+    signature: Option<String>,
+
+    /// Custom RPC URL, used with --signature
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: Option<String>,
+
+    /// Print the decoded events as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Read the logs from a file or RPC and print the decoded events
+pub fn run(args: &EventsArgs) {
+    let idl = resolve_idl(args.idl.as_deref(), args.program.as_deref());
+
+    let logs = match (&args.file, &args.signature, &args.rpc) {
+        (Some(path), _, _) => {
+            let contents = std::fs::read_to_string(path).expect("Failed to read transaction file");
+            let json: Value = serde_json::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("Error parsing transaction file: {}", e);
+                std::process::exit(1);
+            });
+            logs_from_transaction_json(&json).unwrap_or_else(|| {
+                eprintln!("Error: No log messages found in {}", path);
+                std::process::exit(1);
+            })
+        }
+        (None, Some(signature), Some(rpc)) => {
+            let rpc_client = RpcClient::new(rpc.clone());
+            fetch_transaction_logs(&rpc_client, signature).unwrap_or_else(|e| {
+                eprintln!("Error fetching transaction logs: {}", e);
+                std::process::exit(1);
+            })
+        }
+        _ => {
+            eprintln!("Error: Provide either --file or --signature with --rpc");
+            std::process::exit(1);
+        }
+    };
+
+    let events = decode_events(&idl, &logs).unwrap_or_else(|e| {
+        eprintln!("Error decoding events: {}", e);
+        std::process::exit(1);
+    });
+
+    if args.json {
+        let json: Vec<Value> = events
+            .iter()
+            .map(|event| match event {
+                Ok(event) => serde_json::to_value(event).expect("Failed to format JSON"),
+                Err(e) => serde_json::json!({ "error": e }),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json).expect("Failed to format JSON"));
+    } else {
+        println!("Found {} events:", events.len());
+        for event in &events {
+            match event {
+                Ok(event) => println!("  {}", event),
+                Err(e) => println!("  Undecodable: {}", e),
+            }
+        }
+    }
+}
+
+// Find the log messages in a saved transaction, with or without the RPC response wrapper
+fn logs_from_transaction_json(json: &Value) -> Option<Vec<String>> {
+    let logs = ["/meta/logMessages", "/result/meta/logMessages"]
+        .iter()
+        .find_map(|pointer| json.pointer(pointer))
+        .or_else(|| json.is_array().then_some(json))?;
+
+    logs.as_array()?
+        .iter()
+        .map(|log| log.as_str().map(String::from))
+        .collect()
+}
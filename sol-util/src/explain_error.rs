@@ -0,0 +1,56 @@
+use clap::Args;
+use sol_tools::tools::{explain_error_code, parse_error_code};
+use std::path::Path;
+
+use crate::registry::resolve_idl;
+
+/// Arguments for explaining a program error code
+#[derive(Args, Debug)]
+pub struct ExplainErrorArgs {
+    /// Error code (decimal or 0x hex), error log line, or transaction error JSON (inline or a file path)
+    #[arg(value_name = "ERROR")]
+    error: String,
+
+    /// IDL whose errors are checked before Anchor's built-in errors
+    #[arg(short, long = "idl", value_name = "IDL_PATH")]
+    idl: Option<String>,
+
+    /// Program whose registered IDL is checked when --idl is not given
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// Print the explanation as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Resolve the error code and print its name and message
+pub fn run(args: &ExplainErrorArgs) {
+    let input = if Path::new(&args.error).is_file() {
+        std::fs::read_to_string(&args.error).expect("Failed to read error file")
+    } else {
+        args.error.clone()
+    };
+    let code = parse_error_code(&input).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    let idl = (args.idl.is_some() || args.program.is_some())
+        .then(|| resolve_idl(args.idl.as_deref(), args.program.as_deref()));
+    let explained = explain_error_code(idl.as_deref(), code).unwrap_or_else(|e| {
+        eprintln!("Error reading IDL errors: {}", e);
+        std::process::exit(1);
+    });
+
+    match explained {
+        Some(explained) if args.json => {
+            println!("{}", serde_json::to_string_pretty(&explained).expect("Failed to format JSON"))
+        }
+        Some(explained) => println!("{}", explained),
+        None => {
+            eprintln!("Unknown error code {} ({:#x})", code, code);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use clap::Args;
+use serde_json::Value;
+use sol_tools::tools::{decode_account_data, detect_account_type, get_multiple_accounts_batched};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::collections::HashMap;
+
+use crate::build_tx::parse_pubkey;
+use crate::registry::{load_registry, registered_idl};
+
+/// Arguments for fetching accounts by address
+#[derive(Args, Debug)]
+pub struct GetArgs {
+    /// Addresses of the accounts to fetch
+    #[arg(value_name = "PUBKEY", required = true)]
+    pubkeys: Vec<String>,
+
+    /// Custom RPC URL
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: String,
+
+    /// Path to the IDL JSON file used to detect and decode account types (defaults to the IDL registered for each owner)
+    #[arg(short, long = "idl", value_name = "IDL_PATH")]
+    idl: Option<String>,
+
+    /// Print the accounts as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Fetch the accounts in batches and decode each with the IDL account type its discriminator matches
+pub fn run(args: &GetArgs) {
+    let idl = args.idl.as_ref().map(|path| std::fs::read_to_string(path).expect("Failed to read IDL file"));
+    let addresses: Vec<Pubkey> = args.pubkeys.iter().map(|pubkey| parse_pubkey(pubkey)).collect();
+
+    let rpc_client = RpcClient::new(args.rpc.clone());
+    let accounts = get_multiple_accounts_batched(&rpc_client, &addresses).unwrap_or_else(|e| {
+        eprintln!("Error fetching accounts: {}", e);
+        std::process::exit(1);
+    });
+
+    // Without --idl each account is decoded with the IDL registered for the program that owns it
+    let registry = idl.is_none().then(load_registry);
+    let mut owner_idls: HashMap<Pubkey, Option<String>> = HashMap::new();
+    let mut decoded: Vec<Option<(String, Result<Value, String>)>> = Vec::new();
+    for account in &accounts {
+        let idl = match (account, &idl, &registry) {
+            (Some(_), Some(idl), _) => Some(idl.as_str()),
+            (Some(account), None, Some(registry)) => owner_idls
+                .entry(account.owner)
+                .or_insert_with(|| registered_idl(registry, &account.owner.to_string()))
+                .as_deref(),
+            _ => None,
+        };
+        decoded.push(idl.zip(account.as_ref()).and_then(|(idl, account)| decode_with_detected_type(idl, account)));
+    }
+
+    if args.json {
+        let json: Vec<Value> = addresses
+            .iter()
+            .zip(&accounts)
+            .zip(&decoded)
+            .map(|((address, account), decoded)| match account {
+                Some(account) => {
+                    let mut entry = serde_json::json!({
+                        "pubkey": address.to_string(),
+                        "owner": account.owner.to_string(),
+                        "lamports": account.lamports,
+                        "data_length": account.data.len(),
+                    });
+                    match decoded {
+                        Some((name, Ok(data))) => {
+                            entry["type"] = Value::from(name.as_str());
+                            entry["data"] = data.clone();
+                        }
+                        Some((name, Err(e))) => {
+                            entry["type"] = Value::from(name.as_str());
+                            entry["error"] = Value::from(e.as_str());
+                        }
+                        None => {}
+                    }
+                    entry
+                }
+                None => serde_json::json!({ "pubkey": address.to_string(), "error": "Account not found" }),
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json).expect("Failed to format JSON"));
+        return;
+    }
+
+    for ((address, account), decoded) in addresses.iter().zip(&accounts).zip(&decoded) {
+        println!("{}", address);
+        let Some(account) = account else {
+            println!("    Account not found");
+            continue;
+        };
+        println!("    Owner: {}", account.owner);
+        println!("    Lamports: {}", account.lamports);
+        match decoded {
+            Some((name, Ok(data))) => println!(
+                "    {}: {}",
+                name,
+                serde_json::to_string_pretty(data).unwrap_or_default().replace('\n', "\n    ")
+            ),
+            Some((name, Err(e))) => println!("    {}: failed to decode: {}", name, e),
+            None => println!("    Unknown account type ({} bytes)", account.data.len()),
+        }
+    }
+}
+
+// Detect the account's IDL type and decode it, keeping decoding errors for display
+pub fn decode_with_detected_type(idl: &str, account: &Account) -> Option<(String, Result<Value, String>)> {
+    let name = detect_account_type(idl, &account.data).unwrap_or_else(|e| {
+        eprintln!("Error reading accounts from IDL: {}", e);
+        std::process::exit(1);
+    })?;
+    let decoded = decode_account_data(idl, &name, &account.data).map_err(|e| e.to_string());
+    Some((name, decoded))
+}
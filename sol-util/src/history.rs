@@ -0,0 +1,64 @@
+use sol_tools::tools::{fetch_transactions, get_address_history};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::str::FromStr;
+
+use crate::registry::add_registered_programs;
+use crate::tx::decode_known_instructions;
+use crate::SearchArgs;
+
+// Print a merged timeline of the transactions that touched the given accounts
+pub fn print_history(cli: &SearchArgs, idl: &str, accounts: &[Pubkey], limit: usize) {
+    if accounts.is_empty() {
+        return;
+    }
+
+    let before = cli.before.as_deref().map(parse_signature);
+    let until = cli.until.as_deref().map(parse_signature);
+    let rpc_client = RpcClient::new(cli.rpc.clone());
+
+    println!("\nFetching up to {} signatures for each of {} accounts...", limit, accounts.len());
+    let entries = get_address_history(&rpc_client, accounts, limit, before, until, cli.concurrency)
+        .unwrap_or_else(|e| {
+            eprintln!("Error fetching transaction history: {}", e);
+            std::process::exit(1);
+        });
+
+    let transactions = if cli.decode_history {
+        let signatures: Vec<String> = entries.iter().map(|entry| entry.signature.clone()).collect();
+        fetch_transactions(&rpc_client, &signatures, cli.concurrency)
+    } else {
+        Vec::new()
+    };
+    // Instructions of other registered programs in the same transactions are decoded too
+    let mut programs = vec![(cli.program.clone(), idl.to_string())];
+    if cli.decode_history {
+        add_registered_programs(&mut programs);
+    }
+
+    println!("Transaction history ({} transactions):", entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let time = entry.block_time.map(|t| format!(" time {}", t)).unwrap_or_default();
+        let status = if entry.error.is_some() { " [failed]" } else { "" };
+        println!("[slot {}{}] {}{}", entry.slot, time, entry.signature, status);
+        println!("    Accounts: {}", entry.addresses.join(", "));
+
+        match transactions.get(i) {
+            Some(Ok(transaction)) => {
+                for instruction in decode_known_instructions(transaction, &programs) {
+                    println!("    [{}] {}", instruction.position, instruction.display);
+                }
+            }
+            Some(Err(e)) => eprintln!("    Error fetching transaction: {}", e),
+            None => {}
+        }
+    }
+}
+
+// Parse a signature argument, exiting on invalid input
+fn parse_signature(signature: &str) -> Signature {
+    Signature::from_str(signature).unwrap_or_else(|e| {
+        eprintln!("Error: Invalid signature {}: {}", signature, e);
+        std::process::exit(1);
+    })
+}
@@ -0,0 +1,152 @@
+use base64::prelude::*;
+use clap::{ArgGroup, Args};
+use sol_tools::tools::{identify_account_data, AccountTypeMatch};
+use solana_client::rpc_client::RpcClient;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::build_tx::parse_pubkey;
+use crate::registry::{idl_home, load_registry};
+
+/// Arguments for identifying the type of an unknown account
+#[derive(Args, Debug)]
+#[command(group(ArgGroup::new("input").required(true).args(["pubkey", "data", "file"])))]
+pub struct IdentifyArgs {
+    /// Address of the account to fetch and identify (requires --rpc)
+    #[arg(value_name = "PUBKEY", requires = "rpc")]
+    pubkey: Option<String>,
+
+    /// Raw account data as base64, or hex with a 0x prefix
+    #[arg(long = "data", value_name = "DATA")]
+    data: Option<String>,
+
+    /// File containing the raw account data
+    #[arg(long = "file", value_name = "DATA_FILE")]
+    file: Option<String>,
+
+    /// Custom RPC URL
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: Option<String>,
+
+    /// IDL to match against (can be specified multiple times)
+    #[arg(short, long = "idl", value_name = "IDL_PATH")]
+    idls: Vec<String>,
+
+    /// Directory whose IDL JSON files are all matched against (defaults to the IDL home)
+    #[arg(long = "idl-dir", value_name = "IDL_DIR")]
+    idl_dir: Option<String>,
+
+    /// Print the matches as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Match the account data against every IDL and report the account types it could be
+pub fn run(args: &IdentifyArgs) {
+    let data = load_account_data(args);
+
+    let mut idl_paths: Vec<PathBuf> = args.idls.iter().map(PathBuf::from).collect();
+    let idl_dir = args.idl_dir.as_ref().map(PathBuf::from).unwrap_or_else(idl_home);
+    idl_paths.extend(list_idl_files(&idl_dir));
+    // Registered IDLs may live outside the IDL directory
+    idl_paths.extend(load_registry().programs().map(|(_, path)| path.to_path_buf()));
+    let mut seen = HashSet::new();
+    idl_paths.retain(|path| seen.insert(path.clone()));
+    if idl_paths.is_empty() {
+        eprintln!("Error: No IDLs found, use --idl, --idl-dir or the IDL registry to provide them");
+        std::process::exit(1);
+    }
+
+    let mut matches: Vec<(PathBuf, AccountTypeMatch)> = Vec::new();
+    for path in idl_paths {
+        let idl = match std::fs::read_to_string(&path) {
+            Ok(idl) => idl,
+            Err(e) => {
+                eprintln!("Skipping {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        match identify_account_data(&idl, &data) {
+            Ok(Some(matched)) => matches.push((path, matched)),
+            Ok(None) => {}
+            Err(e) => eprintln!("Skipping {}: {}", path.display(), e),
+        }
+    }
+
+    if args.json {
+        let json: Vec<serde_json::Value> = matches
+            .iter()
+            .map(|(path, matched)| {
+                let mut entry = serde_json::to_value(matched).expect("Failed to format JSON");
+                entry["idl"] = serde_json::Value::from(path.display().to_string());
+                entry["size_consistent"] = serde_json::Value::from(matched.is_size_consistent());
+                entry
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json).expect("Failed to format JSON"));
+        return;
+    }
+
+    if matches.is_empty() {
+        println!("No IDL account type matches the {} byte account data", data.len());
+        return;
+    }
+    for (path, matched) in &matches {
+        let consistency = if matched.is_size_consistent() { "" } else { " (size mismatch)" };
+        println!("{}{}", matched, consistency);
+        println!("    IDL: {}", path.display());
+    }
+}
+
+// Read the account data from the RPC, the command line or a file
+fn load_account_data(args: &IdentifyArgs) -> Vec<u8> {
+    if let (Some(pubkey), Some(rpc)) = (&args.pubkey, &args.rpc) {
+        let rpc_client = RpcClient::new(rpc.clone());
+        return rpc_client.get_account_data(&parse_pubkey(pubkey)).unwrap_or_else(|e| {
+            eprintln!("Error fetching account {}: {}", pubkey, e);
+            std::process::exit(1);
+        });
+    }
+    if let Some(file) = &args.file {
+        return std::fs::read(file).expect("Failed to read data file");
+    }
+
+    let data = args.data.as_deref().unwrap_or_default().trim();
+    let decoded = match data.strip_prefix("0x") {
+        Some(hex) => decode_hex(hex),
+        None => BASE64_STANDARD.decode(data).map_err(|e| e.to_string()),
+    };
+    decoded.unwrap_or_else(|e| {
+        eprintln!("Error decoding account data: {}", e);
+        std::process::exit(1);
+    })
+}
+
+// Decode a hex string into bytes
+fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.len().is_multiple_of(2) {
+        return Err("Hex data has an odd number of digits".to_string());
+    }
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| format!("Invalid hex digits '{}'", String::from_utf8_lossy(pair)))
+        })
+        .collect()
+}
+
+// List the JSON files in the IDL directory, which may not exist
+fn list_idl_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    files
+}
@@ -0,0 +1,52 @@
+use clap::Args;
+use sol_tools::tools::diff_idls;
+
+/// Arguments for comparing two versions of an IDL
+#[derive(Args, Debug)]
+pub struct IdlDiffArgs {
+    /// Path to the old IDL JSON file
+    #[arg(value_name = "OLD_IDL")]
+    old: String,
+
+    /// Path to the new IDL JSON file
+    #[arg(value_name = "NEW_IDL")]
+    new: String,
+
+    /// Only report changes that break existing account data or field queries
+    #[arg(long = "breaking-only")]
+    breaking_only: bool,
+
+    /// Print the changes as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Report account and field layout changes between two IDL versions, exiting with status 2 on breaking ones
+pub fn run(args: &IdlDiffArgs) {
+    let old = std::fs::read_to_string(&args.old).expect("Failed to read old IDL file");
+    let new = std::fs::read_to_string(&args.new).expect("Failed to read new IDL file");
+
+    let mut changes = diff_idls(&old, &new).unwrap_or_else(|e| {
+        eprintln!("Error comparing IDLs: {}", e);
+        std::process::exit(1);
+    });
+    if args.breaking_only {
+        changes.retain(|change| change.breaking);
+    }
+    let breaking = changes.iter().filter(|change| change.breaking).count();
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&changes).expect("Failed to format JSON"));
+    } else if changes.is_empty() {
+        println!("No layout changes");
+    } else {
+        for change in &changes {
+            let marker = if change.breaking { "BREAKING" } else { "" };
+            println!("{:<9}{}", marker, change);
+        }
+        println!("\n{} changes, {} breaking", changes.len(), breaking);
+    }
+    if breaking > 0 {
+        std::process::exit(2);
+    }
+}
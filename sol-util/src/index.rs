@@ -0,0 +1,272 @@
+use clap::Args;
+use rusqlite::{params_from_iter, types::Value as SqlValue, Connection};
+use serde_json::Value;
+use sol_tools::tools::{
+    decode_account_data, find_account_discriminator, get_account_field_paths, get_account_names_from_idl,
+    get_program_accounts_with_discrim,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::collections::HashSet;
+use std::error::Error;
+
+use crate::registry::load_idl;
+
+// Columns every account table starts with, ahead of the decoded fields
+const METADATA_COLUMNS: [(&str, &str); 3] = [
+    ("pubkey", "TEXT PRIMARY KEY"),
+    ("lamports", "INTEGER NOT NULL"),
+    ("data_length", "INTEGER NOT NULL"),
+];
+
+// Prefix of the columns holding decoded fields
+const FIELD_COLUMN_PREFIX: &str = "data.";
+
+/// Arguments for indexing program accounts into SQLite
+#[derive(Args, Debug)]
+pub struct IndexArgs {
+    /// Custom RPC URL
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: String,
+
+    /// Path to the IDL JSON file (defaults to the registered or on-chain IDL of the program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH")]
+    idl: Option<String>,
+
+    /// Fetch the on-chain IDL again instead of using the registry or the cached copy
+    #[arg(long = "refresh-idl", conflicts_with = "idl")]
+    refresh_idl: bool,
+
+    /// Program ID of the Solana program
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: String,
+
+    /// Account types to index (defaults to every account in the IDL)
+    #[arg(short = 'n', long = "name", value_name = "ACCOUNT_NAME")]
+    accounts: Vec<String>,
+
+    /// Path to the SQLite database, created if it does not exist
+    #[arg(long = "db", value_name = "DB_PATH", default_value = "accounts.db")]
+    db: String,
+}
+
+// Index every requested account type, refreshing rows that already exist
+pub fn run(args: &IndexArgs) {
+    let rpc_client = RpcClient::new(args.rpc.clone());
+    let idl = load_idl(args.idl.as_deref(), &rpc_client, &args.program, args.refresh_idl);
+
+    let account_names = if args.accounts.is_empty() {
+        get_account_names_from_idl(&idl).unwrap_or_else(|e| {
+            eprintln!("Error reading accounts from IDL: {}", e);
+            std::process::exit(1);
+        })
+    } else {
+        args.accounts.clone()
+    };
+
+    let mut connection = Connection::open(&args.db).unwrap_or_else(|e| {
+        eprintln!("Error opening database {}: {}", args.db, e);
+        std::process::exit(1);
+    });
+
+    for account_name in &account_names {
+        println!("Indexing {} accounts...", account_name);
+        match index_account_type(&mut connection, &rpc_client, &idl, &args.program, account_name) {
+            Ok(count) => println!("Indexed {} {} accounts into {}", count, account_name, args.db),
+            Err(e) => eprintln!("Error indexing {} accounts: {}", account_name, e),
+        }
+    }
+}
+
+// Create the table for an account type and upsert every matching program account into it
+fn index_account_type(
+    connection: &mut Connection,
+    rpc_client: &RpcClient,
+    idl: &str,
+    program: &str,
+    account_name: &str,
+) -> Result<usize, Box<dyn Error>> {
+    let field_paths = get_account_field_paths(idl, account_name)?;
+    create_account_table(connection, account_name, &field_paths)?;
+
+    let accounts = get_program_accounts_with_discrim(rpc_client, program, &find_account_discriminator(idl, account_name)?)?;
+    upsert_accounts(connection, idl, account_name, &field_paths, &accounts)
+}
+
+// Upsert the decoded accounts into the table of their account type, skipping accounts that do not decode
+fn upsert_accounts(
+    connection: &mut Connection,
+    idl: &str,
+    account_name: &str,
+    field_paths: &[(String, Value)],
+    accounts: &[(Pubkey, Account)],
+) -> Result<usize, Box<dyn Error>> {
+    let mut columns = METADATA_COLUMNS.iter().map(|(name, _)| name.to_string()).collect::<Vec<_>>();
+    columns.extend(field_paths.iter().map(|(path, _)| field_column(path)));
+    let quoted_columns: Vec<String> = columns.iter().map(|c| quote_identifier(c)).collect();
+    let placeholders: Vec<String> = (1..=columns.len()).map(|i| format!("?{}", i)).collect();
+    let updates: Vec<String> = quoted_columns
+        .iter()
+        .skip(1)
+        .map(|c| format!("{} = excluded.{}", c, c))
+        .collect();
+    let upsert = format!(
+        "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT(pubkey) DO UPDATE SET {}",
+        quote_identifier(account_name),
+        quoted_columns.join(", "),
+        placeholders.join(", "),
+        updates.join(", "),
+    );
+
+    let transaction = connection.transaction()?;
+    let mut indexed = 0;
+    {
+        let mut statement = transaction.prepare(&upsert)?;
+        for (pubkey, account) in accounts {
+            let decoded = match decode_account_data(idl, account_name, &account.data) {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    eprintln!("Skipping {}: {}", pubkey, e);
+                    continue;
+                }
+            };
+
+            let mut row = vec![
+                SqlValue::Text(pubkey.to_string()),
+                SqlValue::Integer(account.lamports as i64),
+                SqlValue::Integer(account.data.len() as i64),
+            ];
+            for (path, _) in field_paths {
+                let pointer = format!("/{}", path.replace('.', "/"));
+                row.push(decoded.pointer(&pointer).map_or(SqlValue::Null, to_sql_value));
+            }
+
+            statement.execute(params_from_iter(row))?;
+            indexed += 1;
+        }
+    }
+    transaction.commit()?;
+
+    Ok(indexed)
+}
+
+// Create the table for an account type with one column per flattened field path, adding the columns of
+// fields that are new since the table was created
+fn create_account_table(
+    connection: &Connection,
+    account_name: &str,
+    field_paths: &[(String, Value)],
+) -> Result<(), Box<dyn Error>> {
+    let table = quote_identifier(account_name);
+    let columns: Vec<String> = METADATA_COLUMNS
+        .iter()
+        .map(|(name, definition)| format!("{} {}", name, definition))
+        .collect();
+    connection.execute(&format!("CREATE TABLE IF NOT EXISTS {} ({})", table, columns.join(", ")), [])?;
+
+    let existing = connection
+        .prepare(&format!("PRAGMA table_info({})", table))?
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<HashSet<_>, _>>()?;
+    for (path, field_type) in field_paths {
+        let column = field_column(path);
+        if !existing.contains(&column) {
+            connection.execute(
+                &format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    table,
+                    quote_identifier(&column),
+                    sql_column_type(field_type)
+                ),
+                [],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+// Name of the column holding a field, prefixed so that fields cannot collide with the metadata columns
+fn field_column(path: &str) -> String {
+    format!("{}{}", FIELD_COLUMN_PREFIX, path)
+}
+
+// Map an IDL field type to a SQLite column type
+fn sql_column_type(field_type: &Value) -> &'static str {
+    match field_type.as_str() {
+        Some("u8" | "i8" | "u16" | "i16" | "u32" | "i32" | "i64" | "bool") => "INTEGER",
+        // Values that may not fit a signed 64-bit integer are stored as text but still compare numerically
+        Some("u64" | "u128" | "i128") => "NUMERIC",
+        Some("f32" | "f64") => "REAL",
+        _ => "TEXT",
+    }
+}
+
+// Convert a decoded JSON value into a SQLite value, storing composite values as JSON text
+fn to_sql_value(value: &Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                SqlValue::Integer(i)
+            } else if let Some(f) = n.as_f64().filter(|_| n.is_f64()) {
+                SqlValue::Real(f)
+            } else {
+                SqlValue::Text(n.to_string())
+            }
+        }
+        Value::String(s) => SqlValue::Text(s.clone()),
+        composite => SqlValue::Text(composite.to_string()),
+    }
+}
+
+// Quote an identifier so field paths containing dots can be used as column names
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_index_migrates_columns_and_keeps_fields_apart() {
+        let old_idl = r#"{
+            "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [{"name": "amount", "type": "u64"}]}}],
+            "types": []
+        }"#;
+        let new_idl = r#"{
+            "accounts": [{"name": "Vault", "type": {"kind": "struct", "fields": [
+                {"name": "amount", "type": "u64"},
+                {"name": "pubkey", "type": "publicKey"}
+            ]}}],
+            "types": []
+        }"#;
+
+        let address = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut data = find_account_discriminator(new_idl, "Vault").unwrap();
+        data.extend_from_slice(&42u64.to_le_bytes());
+        data.extend_from_slice(owner.as_ref());
+        let accounts = vec![(
+            address,
+            Account { lamports: 7, data, owner: Pubkey::new_unique(), executable: false, rent_epoch: 0 },
+        )];
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        for idl in [old_idl, new_idl] {
+            let field_paths = get_account_field_paths(idl, "Vault").unwrap();
+            create_account_table(&connection, "Vault", &field_paths).unwrap();
+            assert_eq!(upsert_accounts(&mut connection, idl, "Vault", &field_paths, &accounts).unwrap(), 1);
+        }
+
+        let row: (String, i64, i64, i64, String) = connection
+            .query_row(
+                r#"SELECT pubkey, lamports, data_length, "data.amount", "data.pubkey" FROM "Vault""#,
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .unwrap();
+        assert_eq!(row, (address.to_string(), 7, 48, 42, owner.to_string()));
+    }
+}
@@ -0,0 +1,123 @@
+use clap::{Args, ValueEnum};
+use std::collections::HashMap;
+use sol_tools::tools::{account_layout_size, detect_layout_mode, get_account_layout_with_mode, LayoutMode};
+
+use crate::registry::resolve_idl;
+
+/// Layout mode forced from the command line
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum LayoutModeArg {
+    Borsh,
+    ReprC,
+    Packed,
+}
+
+/// Arguments for printing an account's byte layout
+#[derive(Args, Debug)]
+pub struct LayoutArgs {
+    /// Path to the IDL JSON file (defaults to the IDL registered for --program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH", required_unless_present = "program")]
+    idl: Option<String>,
+
+    /// Program whose registered IDL is used when --idl is not given
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// Name of the account to lay out
+    #[arg(short = 'n', long = "name", value_name = "ACCOUNT_NAME")]
+    account: String,
+
+    /// Layout to compute (defaults to the one the IDL declares, Borsh unless it is zero-copy)
+    #[arg(long = "mode", value_enum, value_name = "MODE")]
+    mode: Option<LayoutModeArg>,
+
+    /// Observed data length of a real account to compare with the layout size
+    #[arg(long = "data-len", value_name = "BYTES")]
+    data_len: Option<usize>,
+
+    /// Print the layout as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Print the offset, size and type of every field path in the account
+pub fn run(args: &LayoutArgs) {
+    let idl = resolve_idl(args.idl.as_deref(), args.program.as_deref());
+    let mode = match args.mode {
+        Some(LayoutModeArg::Borsh) => LayoutMode::Borsh,
+        Some(LayoutModeArg::ReprC) => LayoutMode::ReprC,
+        Some(LayoutModeArg::Packed) => LayoutMode::Packed,
+        None => detect_layout_mode(&idl, &args.account).unwrap_or_else(|e| {
+            eprintln!("Error reading layout of {}: {}", args.account, e);
+            std::process::exit(1);
+        }),
+    };
+    let layout = get_account_layout_with_mode(&idl, &args.account, mode).unwrap_or_else(|e| {
+        eprintln!("Error reading layout of {}: {}", args.account, e);
+        std::process::exit(1);
+    });
+    let total_size = account_layout_size(&layout);
+
+    if args.json {
+        let json = serde_json::json!({
+            "account": args.account,
+            "mode": mode,
+            "size": total_size,
+            "data_len": args.data_len,
+            "fields": layout,
+        });
+        println!("{}", serde_json::to_string_pretty(&json).expect("Failed to format JSON"));
+        return;
+    }
+
+    println!("Layout mode: {}", mode);
+    let known = |value: Option<usize>| value.map_or_else(|| "?".to_string(), |v| v.to_string());
+    println!("{:>8}  {:>8}  {:<8}  {:<24}  Path", "Offset", "Size", "Kind", "Type");
+    println!("{:>8}  {:>8}  {:<8}  {:<24}  (discriminator)", 0, 8, "fixed", "[u8; 8]");
+    // End of the previous field within each struct, to show the alignment padding before a field
+    let mut struct_ends: HashMap<&str, usize> = HashMap::from([("", 8)]);
+    for field in &layout {
+        let depth = field.path.matches('.').count();
+        let (parent, name) = field.path.rsplit_once('.').unwrap_or(("", &field.path));
+        if let (Some(offset), Some(&end)) = (field.offset, struct_ends.get(parent)) {
+            if offset > end {
+                println!("{:>8}  {:>8}  {:<8}  {:<24}  {}(padding)", end, offset - end, "padding", "", "  ".repeat(depth));
+            }
+        }
+        if let Some(offset) = field.offset {
+            struct_ends.insert(&field.path, offset);
+            if let Some(size) = field.size {
+                struct_ends.insert(parent, offset + size);
+            }
+        }
+        println!(
+            "{:>8}  {:>8}  {:<8}  {:<24}  {}{}",
+            known(field.offset),
+            known(field.size),
+            if field.is_fixed() { "fixed" } else { "dynamic" },
+            field.field_type,
+            "  ".repeat(depth),
+            name,
+        );
+    }
+
+    if let (Some(size), Some(&end)) = (total_size, struct_ends.get("")) {
+        if size > end {
+            println!("{:>8}  {:>8}  {:<8}  {:<24}  (padding)", end, size - end, "padding", "");
+        }
+    }
+
+    match total_size {
+        Some(size) => println!("\nTotal size: {} bytes", size),
+        None => println!("\nTotal size: dynamic"),
+    }
+    if let Some(data_len) = args.data_len {
+        let comparison = match total_size {
+            Some(size) if size == data_len => "matches the layout".to_string(),
+            Some(size) if size < data_len => format!("{} bytes more than the layout", data_len - size),
+            Some(size) => format!("{} bytes less than the layout", size - data_len),
+            None => "layout size depends on the data".to_string(),
+        };
+        println!("Observed data length: {} bytes ({})", data_len, comparison);
+    }
+}
@@ -1,26 +1,101 @@
 use base64::prelude::*;
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
 use sol_tools::tools::{
-    calculate_discriminator, extract_variable_value, find_accounts_by_criteria, get_program_accounts_with_discrim,
+    extract_variable_value, find_account_discriminator, find_accounts_by_criteria, get_program_accounts_with_discrim,
     get_variable_type_from_idl, encode_value_by_type,
 };
 use solana_client::rpc_client::RpcClient;
 use solana_sdk::{account::Account, pubkey::Pubkey};
 use std::collections::HashMap;
+use std::error::Error;
 use std::fs::File;
 use std::io::Write;
+use std::str::FromStr;
+
+mod build_tx;
+mod census;
+mod decode_tx;
+mod diff;
+mod events;
+mod explain_error;
+mod get;
+mod history;
+mod identify;
+mod idl_diff;
+mod index;
+mod layout;
+mod pda;
+mod registry;
+mod sample;
+mod simulate;
+mod tx;
+mod validate_layout;
+mod watch;
 
 /// CLI for searching Solana accounts by account name, variable path, and value.
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true, arg_required_else_help = true)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    search: Option<SearchArgs>,
+}
+
+/// Additional modes beyond the default account search
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Index program accounts into a local SQLite database
+    Index(index::IndexArgs),
+    /// Compare two saved account dumps field by field
+    Diff(diff::DiffArgs),
+    /// Stream decoded account changes over a websocket subscription
+    Watch(watch::WatchArgs),
+    /// Periodically record a field of the matched accounts to a file
+    Sample(sample::SampleArgs),
+    /// Decode a base64 transaction message
+    DecodeTx(decode_tx::DecodeTxArgs),
+    /// Build an unsigned base64 transaction message from an IDL instruction
+    BuildTx(build_tx::BuildTxArgs),
+    /// Simulate a transaction and decode its logs, events, error and accounts
+    Simulate(simulate::SimulateArgs),
+    /// Decode Anchor events from transaction logs
+    Events(events::EventsArgs),
+    /// Map a custom program error code to its IDL or Anchor error
+    ExplainError(explain_error::ExplainErrorArgs),
+    /// Fetch a confirmed transaction and decode it
+    Tx(tx::TxArgs),
+    /// Derive a PDA from the seeds an IDL declares for an instruction account
+    Pda(pda::PdaArgs),
+    /// Fetch accounts by address and decode them with their detected IDL type
+    Get(get::GetArgs),
+    /// Detect which IDL account type some account data belongs to
+    Identify(identify::IdentifyArgs),
+    /// Count a program's accounts and rent per IDL account type
+    Census(census::CensusArgs),
+    /// Print the byte offset, size and type of every field in an account
+    Layout(layout::LayoutArgs),
+    /// Check real account data against the IDL layout
+    ValidateLayout(validate_layout::ValidateLayoutArgs),
+    /// Report account layout changes between two IDL versions
+    IdlDiff(idl_diff::IdlDiffArgs),
+}
+
+/// Arguments for the default account search
+#[derive(Args, Debug)]
+struct SearchArgs {
     /// Custom RPC URL
     #[arg(short, long = "rpc", value_name = "RPC_URL")]
     rpc: String,
 
-    /// Path to the IDL JSON file
+    /// Path to the IDL JSON file (defaults to the registered or on-chain IDL of the program)
     #[arg(short, long = "idl", value_name = "IDL_PATH")]
-    idl: String,
+    idl: Option<String>,
+
+    /// Fetch the on-chain IDL again instead of using the registry or the cached copy
+    #[arg(long = "refresh-idl", conflicts_with = "idl")]
+    refresh_idl: bool,
 
     /// Program ID of the Solana program
     #[arg(short, long = "program", value_name = "PROGRAM_ID")]
@@ -49,6 +124,26 @@ struct Cli {
     /// Maximum number of accounts to display in the console
     #[arg(long = "limit", value_name = "DISPLAY_LIMIT", default_value = "5")]
     display_limit: usize,
+
+    /// Number of recent signatures to fetch for each matched account
+    #[arg(long = "history", value_name = "SIGNATURES")]
+    history: Option<usize>,
+
+    /// Only include transactions older than this signature in the history
+    #[arg(long = "before", value_name = "SIGNATURE", requires = "history")]
+    before: Option<String>,
+
+    /// Only include transactions newer than this signature in the history
+    #[arg(long = "until", value_name = "SIGNATURE", requires = "history")]
+    until: Option<String>,
+
+    /// Maximum number of concurrent RPC requests when fetching history
+    #[arg(long = "concurrency", value_name = "REQUESTS", default_value = "4")]
+    concurrency: usize,
+
+    /// Fetch each transaction in the history and decode its instructions with the IDL
+    #[arg(long = "decode-history", requires = "history")]
+    decode_history: bool,
 }
 
 /// A constraint with path and value for filtering accounts
@@ -61,8 +156,34 @@ struct PathValueConstraint {
 fn main() {
     let cli = Cli::parse();
 
+    match (cli.command, cli.search) {
+        (Some(Command::Index(args)), _) => index::run(&args),
+        (Some(Command::Diff(args)), _) => diff::run(&args),
+        (Some(Command::Watch(args)), _) => watch::run(&args),
+        (Some(Command::Sample(args)), _) => sample::run(&args),
+        (Some(Command::DecodeTx(args)), _) => decode_tx::run(&args),
+        (Some(Command::BuildTx(args)), _) => build_tx::run(&args),
+        (Some(Command::Simulate(args)), _) => simulate::run(&args),
+        (Some(Command::Events(args)), _) => events::run(&args),
+        (Some(Command::ExplainError(args)), _) => explain_error::run(&args),
+        (Some(Command::Tx(args)), _) => tx::run(&args),
+        (Some(Command::Pda(args)), _) => pda::run(&args),
+        (Some(Command::Get(args)), _) => get::run(&args),
+        (Some(Command::Identify(args)), _) => identify::run(&args),
+        (Some(Command::Census(args)), _) => census::run(&args),
+        (Some(Command::Layout(args)), _) => layout::run(&args),
+        (Some(Command::ValidateLayout(args)), _) => validate_layout::run(&args),
+        (Some(Command::IdlDiff(args)), _) => idl_diff::run(&args),
+        (None, Some(search)) => run_search(&search),
+        (None, None) => unreachable!("clap requires a subcommand or search arguments"),
+    }
+}
+
+// Run the default account search
+fn run_search(cli: &SearchArgs) {
     // Load the IDL
-    let idl = std::fs::read_to_string(&cli.idl).expect("Failed to read IDL file");
+    let rpc_client = RpcClient::new(cli.rpc.clone());
+    let idl = registry::load_idl(cli.idl.as_deref(), &rpc_client, &cli.program, cli.refresh_idl);
 
     // Validate the number of paths and values
     if !cli.variable_paths.is_empty() && cli.variable_paths.len() != cli.values.len() {
@@ -73,10 +194,10 @@ fn main() {
     // Search for accounts
     let accounts = if cli.variable_paths.is_empty() {
         // Just search by account discriminator
-        search_accounts_by_account_name(&cli)
+        search_accounts_by_account_name(cli, &idl)
     } else {
         // Search by multiple path-value pairs
-        search_accounts_with_multiple_criteria(&cli, &idl)
+        search_accounts_with_multiple_criteria(cli, &idl)
     };
     
     // Handle results
@@ -86,15 +207,24 @@ fn main() {
     if let Some(interest) = &cli.interest {
         analyze_variable_of_interest(&accounts, &idl, &cli.account, interest);
     }
+
+    // Show the transactions that touched the matched accounts if requested
+    if let Some(limit) = cli.history {
+        let pubkeys: Vec<Pubkey> = accounts.iter().map(|(pubkey, _)| *pubkey).collect();
+        history::print_history(cli, &idl, &pubkeys, limit);
+    }
 }
 
 // Search accounts by discriminator only
-fn search_accounts_by_account_name(cli: &Cli) -> Vec<(Pubkey, Account)> {
+fn search_accounts_by_account_name(cli: &SearchArgs, idl: &str) -> Vec<(Pubkey, Account)> {
     // Create an RPC client
     let rpc_client = RpcClient::new(cli.rpc.clone());
     
-    // Calculate discriminator for the account name
-    let discriminator = calculate_discriminator(&cli.account);
+    // Look up the discriminator of the account name
+    let discriminator = find_account_discriminator(idl, &cli.account).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
     
     // Search for accounts with just the discriminator
     println!("Searching for all {} accounts...", cli.account);
@@ -110,14 +240,14 @@ fn search_accounts_by_account_name(cli: &Cli) -> Vec<(Pubkey, Account)> {
 }
 
 // Parse constraints from CLI arguments
-fn parse_constraints(cli: &Cli, idl: &str) -> Vec<PathValueConstraint> {
+fn parse_constraints(idl: &str, account_name: &str, paths: &[String], values: &[String]) -> Vec<PathValueConstraint> {
     let mut constraints = Vec::new();
     
-    for (i, path) in cli.variable_paths.iter().enumerate() {
-        let value_str = &cli.values[i];
+    for (i, path) in paths.iter().enumerate() {
+        let value_str = &values[i];
         
         // Get variable type from IDL
-        let variable_type = get_variable_type_from_idl(idl, &cli.account, path)
+        let variable_type = get_variable_type_from_idl(idl, account_name, path)
             .unwrap_or_else(|e| {
                 eprintln!("Error getting type for path {}: {}", path, e);
                 std::process::exit(1);
@@ -131,7 +261,7 @@ fn parse_constraints(cli: &Cli, idl: &str) -> Vec<PathValueConstraint> {
             });
         
         // Get the offset for this variable
-        let offset = sol_tools::tools::get_variable_offset_from_idl(idl, &cli.account, path)
+        let offset = sol_tools::tools::get_variable_offset_from_idl(idl, account_name, path)
             .unwrap_or_else(|e| {
                 eprintln!("Error getting offset for path {}: {}", path, e);
                 std::process::exit(1);
@@ -148,15 +278,15 @@ fn parse_constraints(cli: &Cli, idl: &str) -> Vec<PathValueConstraint> {
 }
 
 // Search accounts with multiple constraints
-fn search_accounts_with_multiple_criteria(cli: &Cli, idl: &str) -> Vec<(Pubkey, Account)> {
+fn search_accounts_with_multiple_criteria(cli: &SearchArgs, idl: &str) -> Vec<(Pubkey, Account)> {
     // Create an RPC client
     let rpc_client = RpcClient::new(cli.rpc.clone());
     
     // Parse all constraints
-    let constraints = parse_constraints(cli, idl);
+    let constraints = parse_constraints(idl, &cli.account, &cli.variable_paths, &cli.values);
     
     if constraints.is_empty() {
-        return search_accounts_by_account_name(cli);
+        return search_accounts_by_account_name(cli, idl);
     }
     
     // Get the first constraint to start the search
@@ -280,6 +410,30 @@ fn save_accounts_to_file(accounts: &[(Pubkey, Account)], path: &str) {
     println!("Full results written to {} in JSON format", path);
 }
 
+// Load accounts from a file written by save_accounts_to_file
+fn load_accounts_from_file(path: &str) -> Result<Vec<(Pubkey, Account)>, Box<dyn Error>> {
+    let contents = std::fs::read_to_string(path)?;
+    let json: serde_json::Value = serde_json::from_str(&contents)?;
+    let entries = json["accounts"]
+        .as_array()
+        .ok_or("Dump does not contain an 'accounts' array")?;
+
+    let mut accounts = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let pubkey = Pubkey::from_str(entry["pubkey"].as_str().ok_or("Account entry has no pubkey")?)?;
+        let account = Account {
+            lamports: entry["lamports"].as_u64().ok_or("Account entry has no lamports")?,
+            data: BASE64_STANDARD.decode(entry["data"].as_str().ok_or("Account entry has no data")?)?,
+            owner: Pubkey::from_str(entry["owner"].as_str().ok_or("Account entry has no owner")?)?,
+            executable: entry["executable"].as_bool().unwrap_or(false),
+            rent_epoch: entry["rent_epoch"].as_u64().unwrap_or(0),
+        };
+        accounts.push((pubkey, account));
+    }
+
+    Ok(accounts)
+}
+
 // Analyze variable of interest
 fn analyze_variable_of_interest(
     accounts: &[(Pubkey, Account)], 
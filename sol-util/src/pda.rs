@@ -0,0 +1,78 @@
+use clap::Args;
+use sol_tools::tools::derive_pda_from_idl;
+use solana_client::rpc_client::RpcClient;
+use std::collections::HashMap;
+
+use crate::build_tx::resolve_program;
+use crate::get::decode_with_detected_type;
+use crate::registry::resolve_idl;
+
+/// Arguments for deriving a PDA from IDL seeds
+#[derive(Args, Debug)]
+pub struct PdaArgs {
+    /// Path to the IDL JSON file (defaults to the IDL registered for the program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH", required_unless_present = "program")]
+    idl: Option<String>,
+
+    /// Name of the instruction account whose seeds are used
+    #[arg(short = 'n', long = "name", value_name = "ACCOUNT_NAME")]
+    account: String,
+
+    /// Seed value given as PATH=VALUE, e.g. owner=<pubkey> or config.fee=25 (can be specified multiple times)
+    #[arg(long = "seed", value_name = "PATH=VALUE")]
+    seeds: Vec<String>,
+
+    /// Program ID of the Solana program (defaults to the address in the IDL)
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// RPC URL used to read account field seeds and to fetch the derived account
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: Option<String>,
+}
+
+// Derive the PDA and, with an RPC URL, show the account stored at it
+pub fn run(args: &PdaArgs) {
+    let idl = resolve_idl(args.idl.as_deref(), args.program.as_deref());
+    let program = resolve_program(&idl, args.program.as_deref());
+
+    let seed_values: HashMap<String, String> = args
+        .seeds
+        .iter()
+        .map(|seed| match seed.split_once('=') {
+            Some((path, value)) => (path.to_string(), value.to_string()),
+            None => {
+                eprintln!("Error: Seed '{}' must be given as PATH=VALUE", seed);
+                std::process::exit(1);
+            }
+        })
+        .collect();
+
+    let rpc_client = args.rpc.as_ref().map(|rpc| RpcClient::new(rpc.clone()));
+    let (address, bump) = derive_pda_from_idl(&idl, &program, &args.account, &seed_values, rpc_client.as_ref())
+        .unwrap_or_else(|e| {
+            eprintln!("Error deriving PDA: {}", e);
+            std::process::exit(1);
+        });
+    println!("{} PDA: {} (bump {})", args.account, address, bump);
+
+    let Some(rpc_client) = rpc_client else {
+        return;
+    };
+    match rpc_client.get_account(&address) {
+        Ok(account) => {
+            println!("Owner: {}", account.owner);
+            println!("Lamports: {}", account.lamports);
+            match decode_with_detected_type(&idl, &account) {
+                Some((name, Ok(decoded))) => println!(
+                    "{}: {}",
+                    name,
+                    serde_json::to_string_pretty(&decoded).unwrap_or_default()
+                ),
+                Some((name, Err(e))) => println!("{}: failed to decode: {}", name, e),
+                None => println!("Data: {} bytes", account.data.len()),
+            }
+        }
+        Err(e) => println!("Account not found: {}", e),
+    }
+}
@@ -0,0 +1,92 @@
+use sol_tools::tools::{get_program_address_from_idl, load_program_idl, IdlRegistry};
+use solana_client::rpc_client::RpcClient;
+use std::path::PathBuf;
+
+use crate::build_tx::parse_pubkey;
+
+// The IDL home holds the registry and, under cache/, the fetched on-chain IDLs. It is SOL_IDL_DIR, or
+// sol-util/idl in the user's data directory by default
+pub fn idl_home() -> PathBuf {
+    if let Ok(dir) = std::env::var("SOL_IDL_DIR") {
+        return PathBuf::from(dir);
+    }
+    dirs_next::data_dir().unwrap_or_else(std::env::temp_dir).join("sol-util").join("idl")
+}
+
+// Load the IDL registry from the IDL home
+pub fn load_registry() -> IdlRegistry {
+    IdlRegistry::load(&idl_home()).unwrap_or_else(|e| {
+        eprintln!("Error loading IDL registry: {}", e);
+        std::process::exit(1);
+    })
+}
+
+// Look up the IDL registered for a program, skipping it with a warning when the file cannot be read
+pub fn registered_idl(registry: &IdlRegistry, program: &str) -> Option<String> {
+    registry.load_idl(program).unwrap_or_else(|e| {
+        eprintln!("Skipping the registered IDL for {}: {}", program, e);
+        None
+    })
+}
+
+// Read the IDL file, or fall back to the IDL registered for the program
+pub fn resolve_idl(idl_path: Option<&str>, program: Option<&str>) -> String {
+    if let Some(path) = idl_path {
+        return std::fs::read_to_string(path).expect("Failed to read IDL file");
+    }
+    let Some(program) = program else {
+        eprintln!("Error: use --idl or --program to select an IDL");
+        std::process::exit(1);
+    };
+    let registry = load_registry();
+    registered_idl(&registry, program).unwrap_or_else(|| {
+        eprintln!("Error: no IDL registered for {} in {}, use --idl to pass one", program, registry.dir.display());
+        std::process::exit(1);
+    })
+}
+
+// Read the IDL file or the registered IDL, then fall back to the program's cached on-chain IDL
+pub fn load_idl(idl_path: Option<&str>, rpc_client: &RpcClient, program: &str, refresh: bool) -> String {
+    if let Some(path) = idl_path {
+        return std::fs::read_to_string(path).expect("Failed to read IDL file");
+    }
+    if !refresh {
+        if let Some(idl) = registered_idl(&load_registry(), program) {
+            return idl;
+        }
+    }
+    load_program_idl(rpc_client, &parse_pubkey(program), &idl_home().join("cache"), refresh).unwrap_or_else(|e| {
+        eprintln!("Error loading on-chain IDL: {}, use --idl to pass an IDL file", e);
+        std::process::exit(1);
+    })
+}
+
+// Pair each given IDL file with its program, then add every registered program they do not cover
+pub fn known_programs(idl_paths: &[String]) -> Vec<(String, String)> {
+    let mut programs: Vec<(String, String)> = idl_paths
+        .iter()
+        .map(|path| {
+            let idl = std::fs::read_to_string(path).expect("Failed to read IDL file");
+            let program = get_program_address_from_idl(&idl).unwrap_or_else(|e| {
+                eprintln!("Error reading program address from {}: {}", path, e);
+                std::process::exit(1);
+            });
+            (program, idl)
+        })
+        .collect();
+
+    add_registered_programs(&mut programs);
+    programs
+}
+
+// Add every registered program and its IDL that is not already in the list, skipping unreadable IDLs
+pub fn add_registered_programs(programs: &mut Vec<(String, String)>) {
+    let registry = load_registry();
+    for (program, _) in registry.programs() {
+        if programs.iter().all(|(known, _)| known != program) {
+            if let Some(idl) = registered_idl(&registry, program) {
+                programs.push((program.to_string(), idl));
+            }
+        }
+    }
+}
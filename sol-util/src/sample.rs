@@ -0,0 +1,198 @@
+use clap::{Args, ValueEnum};
+use sol_tools::tools::{decode_account_data, display_value, find_account_discriminator, get_program_accounts_with_slot};
+use solana_client::rpc_client::RpcClient;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::parse_constraints;
+use crate::registry::load_idl;
+
+/// Output format for sampled rows
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum SampleFormat {
+    Csv,
+    Ndjson,
+}
+
+/// Arguments for sampling a field over time
+#[derive(Args, Debug)]
+pub struct SampleArgs {
+    /// Custom RPC URL
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: String,
+
+    /// Path to the IDL JSON file (defaults to the registered or on-chain IDL of the program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH")]
+    idl: Option<String>,
+
+    /// Fetch the on-chain IDL again instead of using the registry or the cached copy
+    #[arg(long = "refresh-idl", conflicts_with = "idl")]
+    refresh_idl: bool,
+
+    /// Program ID of the Solana program
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: String,
+
+    /// Name of the account to sample
+    #[arg(short = 'n', long = "name", value_name = "ACCOUNT_NAME")]
+    account: String,
+
+    /// Path to the variable in the account (can be specified multiple times)
+    #[arg(long = "path", value_name = "VARIABLE_PATH")]
+    variable_paths: Vec<String>,
+
+    /// Value of the variable to filter on (order must match paths)
+    #[arg(short = 'k', long = "value", value_name = "VARIABLE_VALUE")]
+    values: Vec<String>,
+
+    /// Variable to record on every sample
+    #[arg(short = 's', long, value_name = "INTEREST_VARIABLE")]
+    interest: String,
+
+    /// Time between samples, e.g. 30s, 5m or 1h
+    #[arg(long = "every", value_name = "INTERVAL", default_value = "30s", value_parser = parse_duration)]
+    every: Duration,
+
+    /// Total time to sample for (runs until interrupted if omitted)
+    #[arg(long = "duration", value_name = "DURATION", value_parser = parse_duration)]
+    duration: Option<Duration>,
+
+    /// File to append samples to
+    #[arg(short, long = "output", value_name = "OUTPUT_FILE")]
+    output: String,
+
+    /// Format of the output file
+    #[arg(long = "format", value_enum, default_value = "csv")]
+    format: SampleFormat,
+}
+
+// Fetch the matched accounts on a fixed interval and append one row per account
+pub fn run(args: &SampleArgs) {
+    let rpc_client = RpcClient::new(args.rpc.clone());
+    let idl = load_idl(args.idl.as_deref(), &rpc_client, &args.program, args.refresh_idl);
+
+    if args.variable_paths.len() != args.values.len() {
+        eprintln!("Error: The number of paths and values must match");
+        std::process::exit(1);
+    }
+    let constraints = parse_constraints(&idl, &args.account, &args.variable_paths, &args.values);
+    let variable_filters: Vec<(usize, Vec<u8>)> = constraints.iter().map(|c| (c.offset, c.value.clone())).collect();
+    let discriminator = find_account_discriminator(&idl, &args.account).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+    let pointer = format!("/{}", args.interest.replace('.', "/"));
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&args.output)
+        .expect("Failed to open output file");
+    if matches!(args.format, SampleFormat::Csv) && file.metadata().map(|m| m.len() == 0).unwrap_or(false) {
+        writeln!(file, "timestamp,slot,pubkey,value").expect("Failed to write to output file");
+    }
+
+    let start = Instant::now();
+    let mut samples = 0u32;
+
+    loop {
+        match get_program_accounts_with_slot(&rpc_client, &args.program, &discriminator, &variable_filters) {
+            Ok((slot, accounts)) => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                for (pubkey, account) in &accounts {
+                    let decoded = match decode_account_data(&idl, &args.account, &account.data) {
+                        Ok(decoded) => decoded,
+                        Err(e) => {
+                            eprintln!("Skipping {}: {}", pubkey, e);
+                            continue;
+                        }
+                    };
+                    let Some(value) = decoded.pointer(&pointer).cloned() else {
+                        eprintln!("Error: Field '{}' not found in {}", args.interest, args.account);
+                        std::process::exit(1);
+                    };
+                    let row = match args.format {
+                        SampleFormat::Csv => format!(
+                            "{},{},{},{}",
+                            timestamp,
+                            slot,
+                            pubkey,
+                            csv_field(&display_value(&value))
+                        ),
+                        SampleFormat::Ndjson => serde_json::json!({
+                            "timestamp": timestamp,
+                            "slot": slot,
+                            "pubkey": pubkey.to_string(),
+                            "value": value,
+                        })
+                        .to_string(),
+                    };
+                    writeln!(file, "{}", row).expect("Failed to write to output file");
+                }
+                println!("Sampled {} accounts at slot {}", accounts.len(), slot);
+            }
+            Err(e) => eprintln!("Error fetching accounts: {}", e),
+        }
+
+        samples += 1;
+        let next_sample = args.every * samples;
+        if args.duration.is_some_and(|duration| next_sample > duration) {
+            break;
+        }
+        thread::sleep(next_sample.saturating_sub(start.elapsed()));
+    }
+
+    println!("Samples written to {}", args.output);
+}
+
+// Parse a duration such as 30s, 5m, 1h or a plain number of seconds
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, unit) = value
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((value, ""), |i| value.split_at(i));
+    let amount: u64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}'", value))?;
+    let multiplier = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        _ => return Err(format!("Unknown duration unit '{}', expected s, m, h or d", unit)),
+    };
+    let seconds = amount
+        .checked_mul(multiplier)
+        .ok_or_else(|| format!("Duration '{}' is too large", value))?;
+    if seconds == 0 {
+        return Err("Duration must be greater than zero".to_string());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+// Quote a CSV field if it contains separators or quotes
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert!(parse_duration("0m").is_err());
+        assert!(parse_duration("5w").is_err());
+        assert!(parse_duration("99999999999999999d").unwrap_err().contains("too large"));
+    }
+}
@@ -0,0 +1,154 @@
+use clap::Args;
+use serde_json::Value;
+use sol_tools::tools::{decode_events, explain_error_code, parse_error_code, simulate_message};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+
+use crate::build_tx::{build_message, parse_pubkey, resolve_program, InstructionArgs};
+use crate::get::decode_with_detected_type;
+use crate::registry::resolve_idl;
+
+/// Arguments for simulating a transaction
+#[derive(Args, Debug)]
+pub struct SimulateArgs {
+    /// Base64 encoded transaction message (legacy or v0)
+    #[arg(value_name = "MESSAGE", required_unless_present = "instruction", conflicts_with = "instruction")]
+    message: Option<String>,
+
+    #[command(flatten)]
+    build: Option<InstructionArgs>,
+
+    /// Custom RPC URL
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: String,
+
+    /// IDL used to build the instruction and decode events, errors and accounts (defaults to the IDL registered for --program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH", required_unless_present_any = ["message", "program"])]
+    idl: Option<String>,
+
+    /// Program the IDL belongs to (defaults to the address in the IDL)
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// Account to show after the simulation, decoded with the IDL (can be specified multiple times)
+    #[arg(long = "show-account", value_name = "PUBKEY")]
+    show_accounts: Vec<String>,
+
+    /// Print the simulation result as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Simulate the message and print its logs, events, error and account states
+pub fn run(args: &SimulateArgs) {
+    let idl = (args.idl.is_some() || args.program.is_some())
+        .then(|| resolve_idl(args.idl.as_deref(), args.program.as_deref()));
+
+    // The blockhash is replaced by the node, so a built message can use the default one
+    let message = match (&args.message, &args.build, &idl) {
+        (Some(message), _, _) => message.clone(),
+        (None, Some(build), Some(idl)) => {
+            let program = resolve_program(idl, args.program.as_deref());
+            build_message(build, idl, &program, &Hash::default())
+        }
+        _ => {
+            eprintln!("Error: Provide a message, or --instruction with --idl or --program");
+            std::process::exit(1);
+        }
+    };
+    let addresses: Vec<Pubkey> = args.show_accounts.iter().map(|pubkey| parse_pubkey(pubkey)).collect();
+
+    let rpc_client = RpcClient::new(args.rpc.clone());
+    let simulated = simulate_message(&rpc_client, &message, &addresses).unwrap_or_else(|e| {
+        eprintln!("Error simulating transaction: {}", e);
+        std::process::exit(1);
+    });
+
+    let explained_error = simulated
+        .error
+        .as_ref()
+        .and_then(|error| parse_error_code(&error.to_string()).ok())
+        .and_then(|code| explain_error_code(idl.as_deref(), code).ok().flatten());
+    let events = idl
+        .as_ref()
+        .map(|idl| decode_events(idl, &simulated.logs).unwrap_or_default())
+        .unwrap_or_default();
+    let decoded_accounts: Vec<Option<(String, Value)>> = simulated
+        .accounts
+        .iter()
+        .map(|(_, account)| {
+            let (name, decoded) = decode_with_detected_type(idl.as_deref()?, account.as_ref()?)?;
+            Some((name, decoded.ok()?))
+        })
+        .collect();
+
+    if args.json {
+        let accounts: Vec<Value> = simulated
+            .accounts
+            .iter()
+            .zip(&decoded_accounts)
+            .map(|((address, account), decoded)| match account {
+                Some(account) => serde_json::json!({
+                    "pubkey": address.to_string(),
+                    "lamports": account.lamports,
+                    "owner": account.owner.to_string(),
+                    "type": decoded.as_ref().map(|(name, _)| name),
+                    "data": decoded.as_ref().map(|(_, data)| data),
+                }),
+                None => serde_json::json!({ "pubkey": address.to_string(), "closed": true }),
+            })
+            .collect();
+        let json = serde_json::json!({
+            "slot": simulated.slot,
+            "error": simulated.error,
+            "explained_error": explained_error,
+            "units_consumed": simulated.units_consumed,
+            "logs": simulated.logs,
+            "events": events
+                .iter()
+                .map(|event| match event {
+                    Ok(event) => serde_json::to_value(event).expect("Failed to format JSON"),
+                    Err(e) => serde_json::json!({ "error": e }),
+                })
+                .collect::<Vec<Value>>(),
+            "accounts": accounts,
+        });
+        println!("{}", serde_json::to_string_pretty(&json).expect("Failed to format JSON"));
+        return;
+    }
+
+    println!("Simulated at slot {}", simulated.slot);
+    match &simulated.error {
+        Some(error) => println!("Status: Failed {}", error),
+        None => println!("Status: Success"),
+    }
+    if let Some(explained) = &explained_error {
+        println!("Error: {}", explained);
+    }
+    if let Some(units) = simulated.units_consumed {
+        println!("Compute units: {}", units);
+    }
+    println!("Logs:");
+    for log in &simulated.logs {
+        println!("  {}", log);
+    }
+    if !events.is_empty() {
+        println!("Events:");
+        for event in &events {
+            match event {
+                Ok(event) => println!("  {}", event),
+                Err(e) => println!("  Undecodable: {}", e),
+            }
+        }
+    }
+    if !simulated.accounts.is_empty() {
+        println!("Accounts after simulation:");
+        for ((address, account), decoded) in simulated.accounts.iter().zip(&decoded_accounts) {
+            match (account, decoded) {
+                (None, _) => println!("  {}: closed", address),
+                (Some(_), Some((name, data))) => println!("  {} ({}): {}", address, name, data),
+                (Some(account), None) => println!("  {}: {} lamports, not an IDL account", address, account.lamports),
+            }
+        }
+    }
+}
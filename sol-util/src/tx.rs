@@ -0,0 +1,117 @@
+use clap::Args;
+use serde_json::Value;
+use sol_tools::tools::{
+    decode_idl_instructions, decode_instruction_with_idl, fetch_transaction, DecodedIdlInstruction,
+    DecodedTransaction,
+};
+use solana_client::rpc_client::RpcClient;
+
+use crate::registry::known_programs;
+
+/// Arguments for fetching and decoding a transaction
+#[derive(Args, Debug)]
+pub struct TxArgs {
+    /// Signature of the confirmed transaction
+    #[arg(value_name = "SIGNATURE")]
+    signature: String,
+
+    /// Custom RPC URL
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: String,
+
+    /// IDL used to decode instructions for its program, on top of the registered ones (can be specified multiple times)
+    #[arg(short, long = "idl", value_name = "IDL_PATH")]
+    idls: Vec<String>,
+
+    /// Print the decoded transaction as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+/// An instruction decoded with a known program's IDL, keyed by its position in the transaction
+pub struct IdlDecodedInstruction {
+    pub position: String,
+    pub decoded: Result<Value, String>,
+    pub display: String,
+}
+
+// Fetch the transaction and print it with instructions decoded by the given and registered IDLs
+pub fn run(args: &TxArgs) {
+    let rpc_client = RpcClient::new(args.rpc.clone());
+    let transaction = fetch_transaction(&rpc_client, &args.signature).unwrap_or_else(|e| {
+        eprintln!("Error fetching transaction {}: {}", args.signature, e);
+        std::process::exit(1);
+    });
+
+    let programs = known_programs(&args.idls);
+    let idl_instructions = decode_known_instructions(&transaction, &programs);
+
+    if args.json {
+        let mut json = serde_json::to_value(&transaction).expect("Failed to format JSON");
+        if !programs.is_empty() {
+            json["idl_instructions"] = idl_instructions
+                .iter()
+                .map(|instruction| match &instruction.decoded {
+                    Ok(decoded) => serde_json::json!({ "index": instruction.position, "instruction": decoded }),
+                    Err(e) => serde_json::json!({ "index": instruction.position, "error": e }),
+                })
+                .collect();
+        }
+        println!("{}", serde_json::to_string_pretty(&json).expect("Failed to format JSON"));
+    } else {
+        print!("{}", transaction);
+        if !programs.is_empty() {
+            println!("IDL instructions:");
+            for instruction in &idl_instructions {
+                println!("  [{}] {}", instruction.position, instruction.display);
+            }
+        }
+    }
+}
+
+// Decode top-level and inner instructions that target a program with a known IDL
+pub fn decode_known_instructions(
+    transaction: &DecodedTransaction,
+    programs: &[(String, String)],
+) -> Vec<IdlDecodedInstruction> {
+    let mut decoded_instructions = Vec::new();
+    for (program, idl) in programs {
+        let results = match decode_idl_instructions(idl, program, &transaction.message) {
+            Ok(results) => results,
+            Err(e) => {
+                eprintln!("Skipping the IDL for {}: {}", program, e);
+                continue;
+            }
+        };
+        for (index, result) in results {
+            decoded_instructions.push(to_idl_decoded(index.to_string(), result));
+        }
+
+        for inner in &transaction.inner_instructions {
+            for (i, instruction) in inner.instructions.iter().enumerate() {
+                if instruction.program_id != *program {
+                    continue;
+                }
+                let result = decode_instruction_with_idl(idl, instruction).map_err(|e| e.to_string());
+                decoded_instructions.push(to_idl_decoded(format!("{}.{}", inner.index, i), result));
+            }
+        }
+    }
+    decoded_instructions
+}
+
+// Keep both the JSON and display forms of a decoded instruction
+fn to_idl_decoded(position: String, result: Result<DecodedIdlInstruction, String>) -> IdlDecodedInstruction {
+    match result {
+        Ok(instruction) => IdlDecodedInstruction {
+            position,
+            decoded: Ok(serde_json::to_value(&instruction).expect("Failed to format JSON")),
+            display: instruction.to_string().replace('\n', "\n      "),
+        },
+        Err(e) => IdlDecodedInstruction {
+            position,
+            display: format!("Unrecognized: {}", e),
+            decoded: Err(e),
+        },
+    }
+}
@@ -0,0 +1,132 @@
+use clap::Args;
+use sol_tools::tools::{
+    find_account_discriminator, get_multiple_accounts_batched, get_program_accounts_without_data,
+    validate_account_layout, LayoutMismatch,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::build_tx::{parse_pubkey, resolve_program};
+use crate::registry::resolve_idl;
+
+/// Arguments for checking an IDL layout against real accounts
+#[derive(Args, Debug)]
+pub struct ValidateLayoutArgs {
+    /// Accounts to check (defaults to a sample of the program's accounts of this type)
+    #[arg(value_name = "PUBKEY")]
+    pubkeys: Vec<String>,
+
+    /// Custom RPC URL
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: String,
+
+    /// Path to the IDL JSON file (defaults to the IDL registered for the program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH", required_unless_present = "program")]
+    idl: Option<String>,
+
+    /// Program ID of the Solana program (defaults to the address in the IDL)
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: Option<String>,
+
+    /// Name of the account type to check
+    #[arg(short = 'n', long = "name", value_name = "ACCOUNT_NAME")]
+    account: String,
+
+    /// Number of program accounts to sample when no pubkeys are given
+    #[arg(long = "sample", value_name = "COUNT", default_value_t = 20)]
+    sample: usize,
+
+    /// Print the results as JSON
+    #[arg(long = "json")]
+    json: bool,
+}
+
+// Check each sampled account against the layout and report where the IDL and the data disagree
+pub fn run(args: &ValidateLayoutArgs) {
+    let idl = resolve_idl(args.idl.as_deref(), args.program.as_deref());
+    let rpc_client = RpcClient::new(args.rpc.clone());
+
+    let addresses: Vec<Pubkey> = if args.pubkeys.is_empty() {
+        // Listing the accounts without their data keeps the sample cheap on large programs
+        let program = resolve_program(&idl, args.program.as_deref());
+        let discriminator = find_account_discriminator(&idl, &args.account).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        });
+        let listed = get_program_accounts_without_data(&rpc_client, &program, &discriminator).unwrap_or_else(|e| {
+            eprintln!("Error listing {} accounts: {}", args.account, e);
+            std::process::exit(1);
+        });
+        listed.into_iter().take(args.sample).map(|(pubkey, _)| pubkey).collect()
+    } else {
+        args.pubkeys.iter().map(|pubkey| parse_pubkey(pubkey)).collect()
+    };
+    if addresses.is_empty() {
+        eprintln!("No {} accounts found to check", args.account);
+        std::process::exit(1);
+    }
+
+    let accounts = get_multiple_accounts_batched(&rpc_client, &addresses).unwrap_or_else(|e| {
+        eprintln!("Error fetching accounts: {}", e);
+        std::process::exit(1);
+    });
+
+    let mut results = Vec::new();
+    for (address, account) in addresses.iter().zip(&accounts) {
+        let Some(account) = account else {
+            eprintln!("Skipping {}: account not found", address);
+            continue;
+        };
+        match validate_account_layout(&idl, &args.account, &account.data) {
+            Ok(validation) => results.push((address, validation)),
+            Err(e) => {
+                eprintln!("Error validating {} layout: {}", args.account, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let first_mismatch: Option<&LayoutMismatch> = results
+        .iter()
+        .filter_map(|(_, validation)| validation.mismatch.as_ref())
+        .min_by_key(|mismatch| mismatch.offset);
+
+    if args.json {
+        let json = serde_json::json!({
+            "account": args.account,
+            "checked": results.len(),
+            "first_mismatch": first_mismatch,
+            "accounts": results
+                .iter()
+                .map(|(address, validation)| {
+                    let mut entry = serde_json::to_value(validation).expect("Failed to format JSON");
+                    entry["pubkey"] = serde_json::Value::from(address.to_string());
+                    entry
+                })
+                .collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&json).expect("Failed to format JSON"));
+        if first_mismatch.is_some() {
+            std::process::exit(2);
+        }
+        return;
+    }
+
+    for (address, validation) in &results {
+        let size = match validation.layout_size {
+            Some(size) => format!("{} bytes, layout {}", validation.data_len, size),
+            None => format!("{} bytes, dynamic layout", validation.data_len),
+        };
+        match &validation.mismatch {
+            Some(mismatch) => println!("{}: mismatch at {} [{}]", address, mismatch, size),
+            None => println!("{}: ok [{}]", address, size),
+        }
+    }
+
+    let agreeing = results.iter().filter(|(_, validation)| validation.mismatch.is_none()).count();
+    println!("\n{} of {} {} accounts agree with the IDL layout", agreeing, results.len(), args.account);
+    if let Some(mismatch) = first_mismatch {
+        println!("IDL and data first disagree at {}", mismatch);
+        std::process::exit(2);
+    }
+}
@@ -0,0 +1,127 @@
+use clap::Args;
+use sol_tools::tools::{
+    decode_account_data, diff_account_data, find_account_discriminator, get_program_accounts_with_discrim,
+    subscribe_program_accounts_with_discrim,
+};
+use solana_cli_config::Config;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::{filter_accounts_by_constraint, parse_constraints};
+use crate::registry::load_idl;
+
+/// Arguments for watching program accounts
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// Custom RPC URL, used for the initial snapshot
+    #[arg(short, long = "rpc", value_name = "RPC_URL")]
+    rpc: String,
+
+    /// Websocket URL (derived from the RPC URL if omitted)
+    #[arg(short, long = "ws", value_name = "WS_URL")]
+    ws: Option<String>,
+
+    /// Path to the IDL JSON file (defaults to the registered or on-chain IDL of the program)
+    #[arg(short, long = "idl", value_name = "IDL_PATH")]
+    idl: Option<String>,
+
+    /// Fetch the on-chain IDL again instead of using the registry or the cached copy
+    #[arg(long = "refresh-idl", conflicts_with = "idl")]
+    refresh_idl: bool,
+
+    /// Program ID of the Solana program
+    #[arg(short, long = "program", value_name = "PROGRAM_ID")]
+    program: String,
+
+    /// Name of the account to watch
+    #[arg(short = 'n', long = "name", value_name = "ACCOUNT_NAME")]
+    account: String,
+
+    /// Path to the variable in the account (can be specified multiple times)
+    #[arg(long = "path", value_name = "VARIABLE_PATH")]
+    variable_paths: Vec<String>,
+
+    /// Value of the variable to filter on (order must match paths)
+    #[arg(short = 'k', long = "value", value_name = "VARIABLE_VALUE")]
+    values: Vec<String>,
+}
+
+// Snapshot the matching accounts, then print field-level changes as updates arrive
+pub fn run(args: &WatchArgs) {
+    let rpc_client = RpcClient::new(args.rpc.clone());
+    let idl = load_idl(args.idl.as_deref(), &rpc_client, &args.program, args.refresh_idl);
+
+    if args.variable_paths.len() != args.values.len() {
+        eprintln!("Error: The number of paths and values must match");
+        std::process::exit(1);
+    }
+    let constraints = parse_constraints(&idl, &args.account, &args.variable_paths, &args.values);
+    let discriminator = find_account_discriminator(&idl, &args.account).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    });
+
+    // Take an initial snapshot so the first update for each account can be shown as a diff
+    let mut snapshot = get_program_accounts_with_discrim(&rpc_client, &args.program, &discriminator)
+        .unwrap_or_else(|e| {
+            eprintln!("Error fetching initial accounts: {}", e);
+            Vec::new()
+        });
+    for constraint in &constraints {
+        snapshot = filter_accounts_by_constraint(&snapshot, constraint);
+    }
+    let mut known: HashMap<Pubkey, Vec<u8>> = snapshot.into_iter().map(|(k, a)| (k, a.data)).collect();
+    println!("Loaded {} existing {} accounts", known.len(), args.account);
+
+    let ws_url = args
+        .ws
+        .clone()
+        .unwrap_or_else(|| Config::compute_websocket_url(&args.rpc));
+    let variable_filters: Vec<(usize, Vec<u8>)> = constraints.iter().map(|c| (c.offset, c.value.clone())).collect();
+    let (_subscription, receiver) =
+        subscribe_program_accounts_with_discrim(&ws_url, &args.program, &discriminator, &variable_filters)
+            .unwrap_or_else(|e| {
+                eprintln!("Error subscribing to {}: {}", ws_url, e);
+                std::process::exit(1);
+            });
+    println!("Watching {} accounts via {}...", args.account, ws_url);
+
+    for update in receiver {
+        let slot = update.context.slot;
+        let Ok(pubkey) = Pubkey::from_str(&update.value.pubkey) else {
+            eprintln!("Received update for invalid pubkey {}", update.value.pubkey);
+            continue;
+        };
+        let Some(account) = update.value.account.decode::<Account>() else {
+            eprintln!("Failed to decode account data for {}", pubkey);
+            continue;
+        };
+
+        match known.get(&pubkey) {
+            Some(previous) if *previous == account.data => continue,
+            Some(previous) => match diff_account_data(&idl, &args.account, previous, &account.data) {
+                Ok(changes) => {
+                    println!("[slot {}] {}", slot, pubkey);
+                    for change in changes {
+                        println!("    {}", change);
+                    }
+                }
+                Err(e) => eprintln!("Error decoding update for {}: {}", pubkey, e),
+            },
+            None => match decode_account_data(&idl, &args.account, &account.data) {
+                Ok(decoded) => {
+                    println!("[slot {}] new account {}", slot, pubkey);
+                    println!("{}", serde_json::to_string_pretty(&decoded).unwrap_or_default());
+                }
+                Err(e) => eprintln!("Error decoding new account {}: {}", pubkey, e),
+            },
+        }
+
+        known.insert(pubkey, account.data);
+    }
+
+    eprintln!("Subscription closed");
+    std::process::exit(1);
+}